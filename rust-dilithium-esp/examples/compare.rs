@@ -23,7 +23,7 @@ fn main() {
         info!("VERIFIED");
     } else {
         info!("ERROR");
-        for (sw_byte, hw_byte) in zip(sw_signature.iter(), hw_signature.iter()) {
+        for (sw_byte, hw_byte) in zip(sw_signature.as_bytes(), hw_signature.as_bytes()) {
             if sw_byte == hw_byte {
                 info!("{} == {}", sw_byte, hw_byte);
             } else {
@@ -4,8 +4,8 @@
 use core::iter::repeat;
 use esp_idf_sys::{esp_task_wdt_init, getchar};
 use log::info;
-use rust_dilithium::{make_keys, verify, SEED_SIZE, SIGNATURE_SIZE};
-use rust_dilithium_esp::HardwareAesCounter;
+use rust_dilithium::{cose, make_keys, Dilithium5, SEED_SIZE};
+use rust_dilithium_esp::{Dilithium5HardwareAes, HardwareAesCounter, DEMO_SIGN1_SIZE};
 
 #[no_mangle]
 #[allow(clippy::empty_loop)]
@@ -18,8 +18,8 @@ fn main() {
 
     info!(file!());
 
-    let (pk, _) = make_keys::<HardwareAesCounter>(&[0u8; SEED_SIZE / 2]).unwrap();
-    let mut buf = [0u8; SEED_SIZE / 2 + SIGNATURE_SIZE];
+    let (pk, _) = make_keys::<Dilithium5, HardwareAesCounter>(&[0u8; SEED_SIZE / 2]).unwrap();
+    let mut buf = [0u8; DEMO_SIGN1_SIZE];
 
     loop {
         unsafe {
@@ -41,12 +41,9 @@ fn main() {
                     .unwrap();
             }
         }
-        let message = &buf[..SEED_SIZE / 2];
-        let signature = buf[SEED_SIZE / 2..].try_into().unwrap();
-        if verify::<HardwareAesCounter>(message, &signature, &pk) {
-            info!("verified !");
-        } else {
-            info!("rejected !");
+        match cose::verify1::<Dilithium5HardwareAes>(&buf, b"", &pk) {
+            Ok(_) => info!("verified !"),
+            Err(error) => info!("rejected: {error} !"),
         }
     }
 }
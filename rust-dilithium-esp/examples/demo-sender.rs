@@ -3,8 +3,10 @@
 
 use esp_idf_sys::{esp_task_wdt_init, vTaskDelay};
 use log::info;
-use rust_dilithium::{make_keys, sign, SEED_SIZE};
-use rust_dilithium_esp::{true_random_seed, HardwareAesCounter};
+use rust_dilithium::{cose, make_keys, Dilithium5, SEED_SIZE};
+use rust_dilithium_esp::{
+    true_random_seed, Dilithium5HardwareAes, HardwareAesCounter, DEMO_SIGN1_SIZE,
+};
 
 #[no_mangle]
 #[allow(clippy::empty_loop)]
@@ -17,14 +19,16 @@ fn main() {
 
     info!(file!());
 
-    let (_, sk) = make_keys::<HardwareAesCounter>(&[0u8; SEED_SIZE / 2]).unwrap();
+    let (_, sk) = make_keys::<Dilithium5, HardwareAesCounter>(&[0u8; SEED_SIZE / 2]).unwrap();
+    let mut buf = [0u8; DEMO_SIGN1_SIZE];
 
     loop {
         let message = true_random_seed();
-        let signature = sign::<HardwareAesCounter>(&message, &sk);
+        let sign1 =
+            cose::sign1::<Dilithium5HardwareAes>(&message, b"", &sk, &true_random_seed(), &mut buf)
+                .unwrap();
         unsafe {
-            info!("{message:?}");
-            info!("{signature:?}");
+            info!("{sign1:?}");
             vTaskDelay(100);
         }
     }
@@ -13,11 +13,8 @@ while True:
         try:
             payload = eval(list_str)
                 
-            if len(payload) == 32:
-                print("[jammer] forwarding a new message")
-                reader.write(b"\xff\xff\xff\xff")
-            else:
-                print("[jammer] forwarding the signature")
+            print("[jammer] forwarding a new envelope")
+            reader.write(b"\xff\xff\xff\xff")
 
             if random() < 1 / 4:
                 print("[jammer] introducing an error !")
@@ -1,10 +1,11 @@
 #![no_std]
 #![no_main]
 
-use esp_idf_sys::{dilithium_reference_crypto_sign_keypair, esp_task_wdt_init};
+use esp_idf_sys::esp_task_wdt_init;
 use log::info;
-use rust_dilithium::{counter::SoftwareAesCounter, make_keys, PUBLIC_KEY_SIZE, SECRET_KEY_SIZE};
+use rust_dilithium::{counter::SoftwareAesCounter, make_keys, Dilithium5, ParameterSet};
 use rust_dilithium_esp::{true_random_seed, HardwareAesCounter, Timer};
+use rust_dilithium_ffi::crypto_sign_keypair;
 
 type Chronometer = Timer<0, 0>;
 
@@ -25,7 +26,7 @@ fn main() {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            make_keys::<SoftwareAesCounter>(&true_random_seed());
+            let _ = make_keys::<Dilithium5, SoftwareAesCounter>(&true_random_seed());
         });
         chronometer.get()
     };
@@ -38,7 +39,7 @@ fn main() {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            make_keys::<HardwareAesCounter>(&true_random_seed());
+            let _ = make_keys::<Dilithium5, HardwareAesCounter>(&true_random_seed());
         });
         chronometer.get()
     };
@@ -47,20 +48,20 @@ fn main() {
         hardware_time / TRIALS_NB as u64
     );
 
-    let reference_time = unsafe {
-        let mut pk = [0u8; PUBLIC_KEY_SIZE];
-        let mut sk = [0u8; SECRET_KEY_SIZE];
+    let ffi_time = unsafe {
+        let mut pk = [0u8; Dilithium5::PUBLIC_KEY_SIZE];
+        let mut sk = [0u8; Dilithium5::SECRET_KEY_SIZE];
 
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            dilithium_reference_crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr());
+            crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr());
         });
         chronometer.get()
     };
     info!(
-        "Reference perf: {reference_time} for {TRIALS_NB} iterations ({}/it)",
-        reference_time / TRIALS_NB as u64
+        "FFI perf: {ffi_time} for {TRIALS_NB} iterations ({}/it)",
+        ffi_time / TRIALS_NB as u64
     );
 
     loop {}
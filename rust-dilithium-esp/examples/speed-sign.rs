@@ -1,10 +1,13 @@
 #![no_std]
 #![no_main]
 
-use esp_idf_sys::{dilithium_reference_crypto_sign_signature, esp_task_wdt_init};
+use esp_idf_sys::esp_task_wdt_init;
 use log::info;
-use rust_dilithium::{counter::SoftwareAesCounter, make_keys, sign, SEED_SIZE, SIGNATURE_SIZE};
+use rust_dilithium::{
+    counter::SoftwareAesCounter, make_keys, sign, Dilithium5, ParameterSet, SEED_SIZE,
+};
 use rust_dilithium_esp::{true_random_seed, HardwareAesCounter, Timer};
+use rust_dilithium_ffi::crypto_sign_signature;
 
 type Chronometer = Timer<0, 0>;
 
@@ -21,13 +24,13 @@ fn main() {
 
     info!(file!());
 
-    let (_, sk) = make_keys::<HardwareAesCounter>(&true_random_seed()).unwrap();
+    let (_, sk) = make_keys::<Dilithium5, HardwareAesCounter>(&true_random_seed()).unwrap();
 
     let software_time = {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            sign::<SoftwareAesCounter>(&true_random_seed(), &sk);
+            let _ = sign::<Dilithium5, SoftwareAesCounter>(&true_random_seed(), &sk);
         });
         chronometer.get()
     };
@@ -40,7 +43,7 @@ fn main() {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            sign::<HardwareAesCounter>(&true_random_seed(), &sk);
+            let _ = sign::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &sk);
         });
         chronometer.get()
     };
@@ -49,26 +52,26 @@ fn main() {
         hardware_time / TRIALS_NB as u64
     );
 
-    let reference_time = unsafe {
-        let mut sig = [0u8; SIGNATURE_SIZE];
+    let ffi_time = unsafe {
+        let mut sig = [0u8; Dilithium5::SIGNATURE_SIZE];
         let mut siglen = 0usize;
 
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            dilithium_reference_crypto_sign_signature(
+            crypto_sign_signature(
                 sig.as_mut_ptr(),
                 &mut siglen,
                 true_random_seed().as_ptr(),
                 SEED_SIZE / 2,
-                sk.as_ptr(),
+                sk.as_bytes().as_ptr(),
             );
         });
         chronometer.get()
     };
     info!(
-        "Reference perf: {reference_time} for {TRIALS_NB} iterations ({}/it)",
-        reference_time / TRIALS_NB as u64
+        "FFI perf: {ffi_time} for {TRIALS_NB} iterations ({}/it)",
+        ffi_time / TRIALS_NB as u64
     );
 
     loop {}
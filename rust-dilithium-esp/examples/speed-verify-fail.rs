@@ -1,10 +1,11 @@
 #![no_std]
 #![no_main]
 
-use esp_idf_sys::{dilithium_reference_crypto_sign_verify, esp_task_wdt_init};
+use esp_idf_sys::esp_task_wdt_init;
 use log::info;
-use rust_dilithium::{counter::SoftwareAesCounter, make_keys, sign, verify, SIGNATURE_SIZE};
+use rust_dilithium::{counter::SoftwareAesCounter, make_keys, sign, verify, Dilithium5};
 use rust_dilithium_esp::{true_random_seed, HardwareAesCounter, Timer};
+use rust_dilithium_ffi::crypto_sign_verify;
 
 type Chronometer = Timer<0, 0>;
 
@@ -21,17 +22,18 @@ fn main() {
 
     info!(file!());
 
-    let (pk, sk) = make_keys::<HardwareAesCounter>(&true_random_seed()).unwrap();
-    let mut signature = [0u8; SIGNATURE_SIZE];
+    let (pk, sk) = make_keys::<Dilithium5, HardwareAesCounter>(&true_random_seed()).unwrap();
+    let mut signature = sign::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &sk).unwrap();
 
     let software_time = {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
             chronometer.pause(|| {
-                signature = sign::<HardwareAesCounter>(&true_random_seed(), &sk);
+                signature =
+                    sign::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &sk).unwrap();
             });
-            verify::<SoftwareAesCounter>(&true_random_seed(), &signature, &pk);
+            let _ = verify::<Dilithium5, SoftwareAesCounter>(&true_random_seed(), &signature, &pk);
         });
         chronometer.get()
     };
@@ -45,9 +47,10 @@ fn main() {
 
         (0..TRIALS_NB).for_each(|_| {
             chronometer.pause(|| {
-                signature = sign::<HardwareAesCounter>(&true_random_seed(), &sk);
+                signature =
+                    sign::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &sk).unwrap();
             });
-            verify::<HardwareAesCounter>(&true_random_seed(), &signature, &pk);
+            let _ = verify::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &signature, &pk);
         });
         chronometer.get()
     };
@@ -56,29 +59,30 @@ fn main() {
         hardware_time / TRIALS_NB as u64
     );
 
-    let reference_time = unsafe {
+    let ffi_time = unsafe {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
             let message = true_random_seed();
 
             chronometer.pause(|| {
-                signature = sign::<HardwareAesCounter>(&true_random_seed(), &sk);
+                signature =
+                    sign::<Dilithium5, HardwareAesCounter>(&true_random_seed(), &sk).unwrap();
             });
 
-            dilithium_reference_crypto_sign_verify(
-                signature.as_ptr(),
-                signature.len(),
+            crypto_sign_verify(
+                signature.as_bytes().as_ptr(),
+                signature.as_bytes().len(),
                 message.as_ptr(),
                 message.len(),
-                pk.as_ptr(),
+                pk.as_bytes().as_ptr(),
             );
         });
         chronometer.get()
     };
     info!(
-        "Reference perf: {reference_time} for {TRIALS_NB} iterations ({}/it)",
-        reference_time / TRIALS_NB as u64
+        "FFI perf: {ffi_time} for {TRIALS_NB} iterations ({}/it)",
+        ffi_time / TRIALS_NB as u64
     );
 
     loop {}
@@ -1,12 +1,11 @@
 #![no_std]
 #![no_main]
 
-use esp_idf_sys::{dilithium_reference_crypto_sign_verify, esp_task_wdt_init};
+use esp_idf_sys::esp_task_wdt_init;
 use log::info;
-use rust_dilithium::{
-    counter::SoftwareAesCounter, make_keys, sign, verify, SEED_SIZE, SIGNATURE_SIZE,
-};
+use rust_dilithium::{counter::SoftwareAesCounter, make_keys, sign, verify, Dilithium5, SEED_SIZE};
 use rust_dilithium_esp::{true_random_seed, HardwareAesCounter, Timer};
+use rust_dilithium_ffi::crypto_sign_verify;
 
 type Chronometer = Timer<0, 0>;
 
@@ -23,9 +22,9 @@ fn main() {
 
     info!(file!());
 
-    let (pk, sk) = make_keys::<HardwareAesCounter>(&true_random_seed()).unwrap();
+    let (pk, sk) = make_keys::<Dilithium5, HardwareAesCounter>(&true_random_seed()).unwrap();
     let mut message = [0u8; SEED_SIZE / 2];
-    let mut signature = [0u8; SIGNATURE_SIZE];
+    let mut signature = sign::<Dilithium5, HardwareAesCounter>(&message, &sk).unwrap();
 
     let software_time = {
         let chronometer = Chronometer::start();
@@ -33,9 +32,9 @@ fn main() {
         (0..TRIALS_NB).for_each(|_| {
             chronometer.pause(|| {
                 message = true_random_seed();
-                signature = sign::<HardwareAesCounter>(&message, &sk);
+                signature = sign::<Dilithium5, HardwareAesCounter>(&message, &sk).unwrap();
             });
-            verify::<SoftwareAesCounter>(&message, &signature, &pk);
+            let _ = verify::<Dilithium5, SoftwareAesCounter>(&message, &signature, &pk);
         });
         chronometer.get()
     };
@@ -50,9 +49,9 @@ fn main() {
         (0..TRIALS_NB).for_each(|_| {
             chronometer.pause(|| {
                 message = true_random_seed();
-                signature = sign::<HardwareAesCounter>(&message, &sk);
+                signature = sign::<Dilithium5, HardwareAesCounter>(&message, &sk).unwrap();
             });
-            verify::<HardwareAesCounter>(&message, &signature, &pk);
+            let _ = verify::<Dilithium5, HardwareAesCounter>(&message, &signature, &pk);
         });
         chronometer.get()
     };
@@ -61,27 +60,27 @@ fn main() {
         hardware_time / TRIALS_NB as u64
     );
 
-    let reference_time = unsafe {
+    let ffi_time = unsafe {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
             chronometer.pause(|| {
                 message = true_random_seed();
-                signature = sign::<HardwareAesCounter>(&message, &sk);
+                signature = sign::<Dilithium5, HardwareAesCounter>(&message, &sk).unwrap();
             });
-            dilithium_reference_crypto_sign_verify(
-                signature.as_ptr(),
-                signature.len(),
+            crypto_sign_verify(
+                signature.as_bytes().as_ptr(),
+                signature.as_bytes().len(),
                 message.as_ptr(),
                 message.len(),
-                pk.as_ptr(),
+                pk.as_bytes().as_ptr(),
             );
         });
         chronometer.get()
     };
     info!(
-        "Reference perf: {reference_time} for {TRIALS_NB} iterations ({}/it)",
-        reference_time / TRIALS_NB as u64
+        "FFI perf: {ffi_time} for {TRIALS_NB} iterations ({}/it)",
+        ffi_time / TRIALS_NB as u64
     );
 
     loop {}
@@ -4,7 +4,7 @@
 use esp_idf_sys::esp_task_wdt_init;
 use log::info;
 use rust_dilithium_esp::{
-    compute_hardware, compute_reference, compute_software, true_random_seed, Timer,
+    compute_ffi, compute_hardware, compute_software, true_random_seed, Timer,
 };
 
 type Chronometer = Timer<0, 0>;
@@ -48,17 +48,17 @@ fn main() {
         hardware_time / TRIALS_NB as u64
     );
 
-    let reference_time = {
+    let ffi_time = {
         let chronometer = Chronometer::start();
 
         (0..TRIALS_NB).for_each(|_| {
-            compute_reference(&true_random_seed());
+            compute_ffi(&true_random_seed());
         });
         chronometer.get()
     };
     info!(
-        "Reference perf: {reference_time} for {TRIALS_NB} iterations ({}/it)",
-        reference_time / TRIALS_NB as u64
+        "FFI perf: {ffi_time} for {TRIALS_NB} iterations ({}/it)",
+        ffi_time / TRIALS_NB as u64
     );
 
     loop {}
@@ -2,18 +2,18 @@
 
 use core::mem::{size_of, transmute, MaybeUninit};
 use esp_idf_sys::{
-    dilithium_reference_crypto_sign_keypair, dilithium_reference_crypto_sign_signature,
-    dilithium_reference_crypto_sign_verify, esp_aes_context, esp_aes_crypt_ecb, esp_aes_init,
-    esp_aes_setkey, esp_fill_random, timer_alarm_t_TIMER_ALARM_DIS,
-    timer_autoreload_t_TIMER_AUTORELOAD_DIS, timer_config_t, timer_count_dir_t_TIMER_COUNT_UP,
-    timer_deinit, timer_get_counter_value, timer_init, timer_intr_t_TIMER_INTR_NONE, timer_pause,
-    timer_set_counter_value, timer_src_clk_t_TIMER_SRC_CLK_APB, timer_start,
-    timer_start_t_TIMER_PAUSE,
+    esp_aes_context, esp_aes_crypt_ecb, esp_aes_init, esp_aes_setkey, esp_fill_random,
+    timer_alarm_t_TIMER_ALARM_DIS, timer_autoreload_t_TIMER_AUTORELOAD_DIS, timer_config_t,
+    timer_count_dir_t_TIMER_COUNT_UP, timer_deinit, timer_get_counter_value, timer_init,
+    timer_intr_t_TIMER_INTR_NONE, timer_pause, timer_set_counter_value,
+    timer_src_clk_t_TIMER_SRC_CLK_APB, timer_start, timer_start_t_TIMER_PAUSE,
 };
 use rust_dilithium::{
+    cose::{self, DilithiumCoseAlgorithm},
     counter::{Counter, SoftwareAesCounter, BLOCK_SIZE, KEY_SIZE},
-    make_keys, sign, verify, Seed, Signature, PUBLIC_KEY_SIZE, SECRET_KEY_SIZE, SIGNATURE_SIZE,
+    make_keys, sign, verify, Dilithium5, ParameterSet, Seed, Signature, SEED_SIZE,
 };
+use rust_dilithium_ffi::{crypto_sign_keypair, crypto_sign_signature, crypto_sign_verify};
 
 pub struct HardwareAesCounter {
     ctx: esp_aes_context,
@@ -78,6 +78,20 @@ impl Counter for HardwareAesCounter {
     }
 }
 
+/// Dilithium5-AES with the AES peripheral, under the private-use COSE algorithm identifier that
+/// the demo examples agree on
+pub struct Dilithium5HardwareAes;
+
+impl DilithiumCoseAlgorithm for Dilithium5HardwareAes {
+    type ParameterSet = Dilithium5;
+    type Counter = HardwareAesCounter;
+
+    const ALGORITHM: i64 = -65537;
+}
+
+/// Size of the `COSE_Sign1` envelopes of the demo examples, whose payloads are seed-sized messages
+pub const DEMO_SIGN1_SIZE: usize = cose::sign1_size::<Dilithium5HardwareAes>(SEED_SIZE / 2);
+
 pub struct Timer<const GROUP: u32, const TIMER: u32>;
 
 impl<const GROUP: u32, const TIMER: u32> Timer<GROUP, TIMER> {
@@ -129,10 +143,10 @@ impl<const GROUP: u32, const TIMER: u32> Drop for Timer<GROUP, TIMER> {
 }
 
 #[inline(never)]
-pub fn compute_software(msg: &[u8], seed: &Seed) -> Option<Signature> {
-    let (pk, sk) = make_keys::<SoftwareAesCounter>(seed).unwrap();
-    let signature = sign::<SoftwareAesCounter>(msg, &sk);
-    if verify::<SoftwareAesCounter>(msg, &signature, &pk) {
+pub fn compute_software(msg: &[u8], seed: &Seed) -> Option<Signature<Dilithium5>> {
+    let (pk, sk) = make_keys::<Dilithium5, SoftwareAesCounter>(seed).unwrap();
+    let signature = sign::<Dilithium5, SoftwareAesCounter>(msg, &sk).unwrap();
+    if verify::<Dilithium5, SoftwareAesCounter>(msg, &signature, &pk).is_ok() {
         Some(signature)
     } else {
         None
@@ -140,10 +154,10 @@ pub fn compute_software(msg: &[u8], seed: &Seed) -> Option<Signature> {
 }
 
 #[inline(never)]
-pub fn compute_hardware(msg: &[u8], seed: &Seed) -> Option<Signature> {
-    let (pk, sk) = make_keys::<HardwareAesCounter>(seed).unwrap();
-    let signature = sign::<HardwareAesCounter>(msg, &sk);
-    if verify::<HardwareAesCounter>(msg, &signature, &pk) {
+pub fn compute_hardware(msg: &[u8], seed: &Seed) -> Option<Signature<Dilithium5>> {
+    let (pk, sk) = make_keys::<Dilithium5, HardwareAesCounter>(seed).unwrap();
+    let signature = sign::<Dilithium5, HardwareAesCounter>(msg, &sk).unwrap();
+    if verify::<Dilithium5, HardwareAesCounter>(msg, &signature, &pk).is_ok() {
         Some(signature)
     } else {
         None
@@ -151,24 +165,24 @@ pub fn compute_hardware(msg: &[u8], seed: &Seed) -> Option<Signature> {
 }
 
 #[inline(never)]
-pub fn compute_reference(msg: &[u8]) -> Option<Signature> {
+pub fn compute_ffi(msg: &[u8]) -> Option<Signature<Dilithium5>> {
     // The compiler might not ellide these variables initialization, which would cause an overhead.
     // However, it is negligible compared to the execution time of the algorithm.
-    let mut pk = [0u8; PUBLIC_KEY_SIZE];
-    let mut sk = [0u8; SECRET_KEY_SIZE];
-    let mut sig = [0u8; SIGNATURE_SIZE];
+    let mut pk = [0u8; Dilithium5::PUBLIC_KEY_SIZE];
+    let mut sk = [0u8; Dilithium5::SECRET_KEY_SIZE];
+    let mut sig = [0u8; Dilithium5::SIGNATURE_SIZE];
     let mut siglen = 0usize;
 
     unsafe {
-        dilithium_reference_crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr());
-        dilithium_reference_crypto_sign_signature(
+        crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr());
+        crypto_sign_signature(
             sig.as_mut_ptr(),
             &mut siglen,
             msg.as_ptr(),
             msg.len(),
             sk.as_ptr(),
         );
-        if dilithium_reference_crypto_sign_verify(
+        if crypto_sign_verify(
             sig.as_mut_ptr(),
             siglen,
             msg.as_ptr(),
@@ -176,7 +190,7 @@ pub fn compute_reference(msg: &[u8]) -> Option<Signature> {
             pk.as_mut_ptr(),
         ) == 0
         {
-            Some(sig)
+            Signature::from_bytes(&sig).ok()
         } else {
             None
         }
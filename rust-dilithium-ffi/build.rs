@@ -0,0 +1,61 @@
+use rust_dilithium::{Dilithium2, Dilithium3, Dilithium5, ParameterSet};
+use std::{env, fs, path::PathBuf};
+
+const RANDOMBYTES_HOOK: &str = "\
+/* Provided by the caller, fills `out` with `outlen` bytes of fresh randomness. It is prefixed to
+   avoid the `randombytes` of some ESP-IDF components. */
+void dilithium_randombytes(uint8_t *out, size_t outlen);";
+
+const RANDOMBYTES_ESP: &str = "\
+/* The key pairs are derived from seeds drawn from `esp_fill_random` */";
+
+/// Generates the header of the exported functions, with the sizes of the selected parameter set
+///
+/// The header is written to `include/dilithium.h` in `OUT_DIR`, which dependent build scripts find
+/// in `DEP_DILITHIUM_INCLUDE`, and copied to `DILITHIUM_INCLUDE_DIR` if it is set.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{name}")).is_some();
+    let (mode, sizes) = match (feature("MODE2"), feature("MODE3"), feature("MODE5")) {
+        (true, false, false) => (2, sizes::<Dilithium2>()),
+        (false, true, false) => (3, sizes::<Dilithium3>()),
+        (false, false, true) => (5, sizes::<Dilithium5>()),
+        // Reported by the library itself
+        _ => return Ok(()),
+    };
+    let name = format!(
+        "Dilithium{mode}{}",
+        if feature("AES") { "-AES" } else { "" }
+    );
+    let header = format!(
+        include_str!("header.h.in"),
+        name = name,
+        public_key_size = sizes[0],
+        secret_key_size = sizes[1],
+        signature_size = sizes[2],
+        randomness = if feature("ESP") {
+            RANDOMBYTES_ESP
+        } else {
+            RANDOMBYTES_HOOK
+        },
+    );
+
+    let include_dir = PathBuf::from(env::var("OUT_DIR")?).join("include");
+    fs::create_dir_all(&include_dir)?;
+    fs::write(include_dir.join("dilithium.h"), &header)?;
+
+    if let Some(dir) = env::var_os("DILITHIUM_INCLUDE_DIR") {
+        fs::create_dir_all(&dir)?;
+        fs::write(PathBuf::from(dir).join("dilithium.h"), &header)?;
+    }
+
+    println!("cargo:include={}", include_dir.display());
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=header.h.in");
+    println!("cargo:rerun-if-env-changed=DILITHIUM_INCLUDE_DIR");
+
+    Ok(())
+}
+
+fn sizes<P: ParameterSet>() -> [usize; 3] {
+    [P::PUBLIC_KEY_SIZE, P::SECRET_KEY_SIZE, P::SIGNATURE_SIZE]
+}
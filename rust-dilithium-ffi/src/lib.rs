@@ -0,0 +1,244 @@
+//! C ABI of `rust-dilithium`, with the function names and the signatures of the CRYSTALS-Dilithium
+//! reference implementation
+//!
+//! The parameter set is selected at build time by the `mode2`, `mode3` and `mode5` features, and
+//! the AES variant by the `aes` feature, as `DILITHIUM_MODE` and `DILITHIUM_USE_AES` do for the
+//! reference implementation. The build script generates the matching `dilithium.h`, see its
+//! documentation for where it is written. As in the reference implementation, the signatures are
+//! deterministic.
+//!
+//! The seeds of the key pairs are drawn from `dilithium_randombytes`, which the caller provides
+//! under a prefixed name since `randombytes` clashes with some ESP-IDF components, or from
+//! `esp_fill_random` with the `esp` feature.
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+use core::{ptr, slice};
+use rust_dilithium::{
+    make_keys, open_attached, sign, verify, ParameterSet, PublicKey, SecretKey, Seed, Signature,
+};
+use zeroize::Zeroizing;
+
+#[cfg(not(any(
+    all(feature = "mode2", not(feature = "mode3"), not(feature = "mode5")),
+    all(feature = "mode3", not(feature = "mode2"), not(feature = "mode5")),
+    all(feature = "mode5", not(feature = "mode2"), not(feature = "mode3")),
+)))]
+compile_error!("exactly one of the features `mode2`, `mode3` and `mode5` must be enabled");
+
+#[cfg(feature = "mode2")]
+type P = rust_dilithium::Dilithium2;
+#[cfg(feature = "mode3")]
+type P = rust_dilithium::Dilithium3;
+#[cfg(feature = "mode5")]
+type P = rust_dilithium::Dilithium5;
+
+#[cfg(feature = "aes")]
+type Ctr = rust_dilithium::counter::SoftwareAesCounter;
+#[cfg(not(feature = "aes"))]
+type Ctr = rust_dilithium::counter::ShakeCounter;
+
+pub const CRYPTO_PUBLICKEYBYTES: usize = P::PUBLIC_KEY_SIZE;
+pub const CRYPTO_SECRETKEYBYTES: usize = P::SECRET_KEY_SIZE;
+pub const CRYPTO_BYTES: usize = P::SIGNATURE_SIZE;
+
+const SUCCESS: i32 = 0;
+const FAILURE: i32 = -1;
+
+#[cfg(not(feature = "esp"))]
+extern "C" {
+    fn dilithium_randombytes(out: *mut u8, outlen: usize);
+}
+
+#[cfg(feature = "esp")]
+extern "C" {
+    fn esp_fill_random(buf: *mut core::ffi::c_void, len: usize);
+}
+
+#[cfg(not(any(test, feature = "std")))]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    extern "C" {
+        fn abort() -> !;
+    }
+
+    unsafe { abort() }
+}
+
+/// Draws a seed, and writes the key pair derived from it to `pk` and `sk`
+///
+/// # Safety
+///
+/// `pk` and `sk` must be valid for writes of `CRYPTO_PUBLICKEYBYTES` and `CRYPTO_SECRETKEYBYTES`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> i32 {
+    let mut seed = Zeroizing::new(Seed::default());
+    fill_random(seed.as_mut());
+
+    let Ok((public_key, secret_key)) = make_keys::<P, Ctr>(&seed) else {
+        return FAILURE;
+    };
+
+    write(pk, public_key.as_bytes());
+    write(sk, secret_key.as_bytes());
+
+    SUCCESS
+}
+
+/// Signs the `mlen` bytes at `m`, and writes the signature to `sig` and its size to `siglen`
+///
+/// # Safety
+///
+/// `sig` must be valid for writes of `CRYPTO_BYTES` bytes, `m` for reads of `mlen` bytes, and `sk`
+/// for reads of `CRYPTO_SECRETKEYBYTES` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_sign_signature(
+    sig: *mut u8,
+    siglen: *mut usize,
+    m: *const u8,
+    mlen: usize,
+    sk: *const u8,
+) -> i32 {
+    let Ok(secret_key) = SecretKey::<P>::from_bytes(read(sk, CRYPTO_SECRETKEYBYTES)) else {
+        return FAILURE;
+    };
+    let Ok(signature) = sign::<P, Ctr>(read(m, mlen), &secret_key) else {
+        return FAILURE;
+    };
+
+    write(sig, signature.as_bytes());
+    *siglen = CRYPTO_BYTES;
+
+    SUCCESS
+}
+
+/// Signs the `mlen` bytes at `m`, and writes the signed message `signature || m` to `sm` and its
+/// size to `smlen`
+///
+/// # Safety
+///
+/// `sm` must be valid for writes of `CRYPTO_BYTES + mlen` bytes, `m` for reads of `mlen` bytes, and
+/// `sk` for reads of `CRYPTO_SECRETKEYBYTES` bytes. As in the reference implementation, `m` may
+/// overlap `sm`.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_sign(
+    sm: *mut u8,
+    smlen: *mut usize,
+    m: *const u8,
+    mlen: usize,
+    sk: *const u8,
+) -> i32 {
+    // The message is moved first, which the reference implementation also does
+    if mlen > 0 {
+        ptr::copy(m, sm.add(CRYPTO_BYTES), mlen);
+    }
+
+    let Ok(secret_key) = SecretKey::<P>::from_bytes(read(sk, CRYPTO_SECRETKEYBYTES)) else {
+        return FAILURE;
+    };
+    let Ok(signature) = sign::<P, Ctr>(read(sm.add(CRYPTO_BYTES), mlen), &secret_key) else {
+        return FAILURE;
+    };
+
+    write(sm, signature.as_bytes());
+    *smlen = CRYPTO_BYTES + mlen;
+
+    SUCCESS
+}
+
+/// Verifies the signature of `siglen` bytes at `sig` against the `mlen` bytes at `m`, and returns
+/// 0 if it is valid
+///
+/// # Safety
+///
+/// `sig` must be valid for reads of `siglen` bytes, `m` for reads of `mlen` bytes, and `pk` for
+/// reads of `CRYPTO_PUBLICKEYBYTES` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_sign_verify(
+    sig: *const u8,
+    siglen: usize,
+    m: *const u8,
+    mlen: usize,
+    pk: *const u8,
+) -> i32 {
+    let Ok(public_key) = PublicKey::<P>::from_bytes(read(pk, CRYPTO_PUBLICKEYBYTES)) else {
+        return FAILURE;
+    };
+    let Ok(signature) = Signature::<P>::from_bytes(read(sig, siglen)) else {
+        return FAILURE;
+    };
+
+    match verify::<P, Ctr>(read(m, mlen), &signature, &public_key) {
+        Ok(()) => SUCCESS,
+        Err(_) => FAILURE,
+    }
+}
+
+/// Verifies the signed message of `smlen` bytes at `sm`, and writes its message to `m` and its
+/// size to `mlen`
+///
+/// On failure, `*mlen` is set to `SIZE_MAX` and the first `smlen` bytes of `m` are zeroed, as in
+/// the reference implementation.
+///
+/// # Safety
+///
+/// `m` must be valid for writes of `smlen` bytes, `sm` for reads of `smlen` bytes, and `pk` for
+/// reads of `CRYPTO_PUBLICKEYBYTES` bytes. As in the reference implementation, `m` may overlap
+/// `sm`.
+#[no_mangle]
+pub unsafe extern "C" fn crypto_sign_open(
+    m: *mut u8,
+    mlen: *mut usize,
+    sm: *const u8,
+    smlen: usize,
+    pk: *const u8,
+) -> i32 {
+    let Ok(public_key) = PublicKey::<P>::from_bytes(read(pk, CRYPTO_PUBLICKEYBYTES)) else {
+        return reject_open(m, mlen, smlen);
+    };
+    let Ok(msg_len) = open_attached::<P, Ctr>(read(sm, smlen), &public_key).map(<[u8]>::len) else {
+        return reject_open(m, mlen, smlen);
+    };
+
+    // The message is copied once `sm` is no longer borrowed, since `m` may overlap it
+    ptr::copy(sm.add(CRYPTO_BYTES), m, msg_len);
+    *mlen = msg_len;
+
+    SUCCESS
+}
+
+/// Clears the output of [`crypto_sign_open`] after a failure
+unsafe fn reject_open(m: *mut u8, mlen: *mut usize, smlen: usize) -> i32 {
+    if smlen > 0 {
+        ptr::write_bytes(m, 0, smlen);
+    }
+    *mlen = usize::MAX;
+
+    FAILURE
+}
+
+/// Fills `buf` with fresh randomness from the source selected by the `esp` feature
+unsafe fn fill_random(buf: &mut [u8]) {
+    #[cfg(not(feature = "esp"))]
+    dilithium_randombytes(buf.as_mut_ptr(), buf.len());
+    #[cfg(feature = "esp")]
+    esp_fill_random(buf.as_mut_ptr().cast(), buf.len());
+}
+
+/// Borrows the `len` bytes at `ptr`, which may be null if `len` is zero
+unsafe fn read<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// Copies `bytes` to `ptr`
+unsafe fn write(ptr: *mut u8, bytes: &[u8]) {
+    ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,166 @@
+use crate::*;
+
+const SEED_BYTE: u8 = 0x2a;
+
+#[no_mangle]
+extern "C" fn dilithium_randombytes(out: *mut u8, outlen: usize) {
+    unsafe { ptr::write_bytes(out, SEED_BYTE, outlen) };
+}
+
+fn keypair() -> ([u8; CRYPTO_PUBLICKEYBYTES], [u8; CRYPTO_SECRETKEYBYTES]) {
+    let mut pk = [0u8; CRYPTO_PUBLICKEYBYTES];
+    let mut sk = [0u8; CRYPTO_SECRETKEYBYTES];
+
+    assert_eq!(
+        unsafe { crypto_sign_keypair(pk.as_mut_ptr(), sk.as_mut_ptr()) },
+        SUCCESS
+    );
+
+    (pk, sk)
+}
+
+#[test]
+fn test_keypair() {
+    let (pk, sk) = keypair();
+    let (public_key, secret_key) = make_keys::<P, Ctr>(&[SEED_BYTE; 32]).unwrap();
+
+    assert_eq!(pk, public_key.as_bytes());
+    assert_eq!(sk, secret_key.as_bytes());
+}
+
+#[test]
+fn test_signature() {
+    let (pk, sk) = keypair();
+    let msg = b"message";
+    let mut sig = [0u8; CRYPTO_BYTES];
+    let mut siglen = 0;
+
+    unsafe {
+        assert_eq!(
+            crypto_sign_signature(
+                sig.as_mut_ptr(),
+                &mut siglen,
+                msg.as_ptr(),
+                msg.len(),
+                sk.as_ptr()
+            ),
+            SUCCESS
+        );
+        assert_eq!(siglen, CRYPTO_BYTES);
+
+        let secret_key = SecretKey::<P>::from_bytes(&sk).unwrap();
+        assert_eq!(sig, sign::<P, Ctr>(msg, &secret_key).unwrap().as_bytes());
+
+        let verify = |sig: &[u8], msg: &[u8]| {
+            crypto_sign_verify(
+                sig.as_ptr(),
+                sig.len(),
+                msg.as_ptr(),
+                msg.len(),
+                pk.as_ptr(),
+            )
+        };
+
+        assert_eq!(verify(&sig, msg), SUCCESS);
+        assert_eq!(verify(&sig, b"massage"), FAILURE);
+        assert_eq!(verify(&sig[1..], msg), FAILURE);
+
+        // The empty message may be given as a null pointer
+        assert_eq!(
+            crypto_sign_signature(sig.as_mut_ptr(), &mut siglen, ptr::null(), 0, sk.as_ptr()),
+            SUCCESS
+        );
+        assert_eq!(
+            crypto_sign_verify(sig.as_ptr(), siglen, ptr::null(), 0, pk.as_ptr()),
+            SUCCESS
+        );
+    }
+}
+
+#[test]
+fn test_sign_open() {
+    let (pk, sk) = keypair();
+    let msg = b"message";
+    let mut sm = [0u8; CRYPTO_BYTES + 7];
+    let mut smlen = 0;
+    let mut m = [0xffu8; CRYPTO_BYTES + 7];
+    let mut mlen = 0;
+
+    unsafe {
+        assert_eq!(
+            crypto_sign(
+                sm.as_mut_ptr(),
+                &mut smlen,
+                msg.as_ptr(),
+                msg.len(),
+                sk.as_ptr()
+            ),
+            SUCCESS
+        );
+        assert_eq!(smlen, sm.len());
+
+        let public_key = PublicKey::<P>::from_bytes(&pk).unwrap();
+        assert_eq!(open_attached::<P, Ctr>(&sm, &public_key).unwrap(), msg);
+
+        assert_eq!(
+            crypto_sign_open(m.as_mut_ptr(), &mut mlen, sm.as_ptr(), smlen, pk.as_ptr()),
+            SUCCESS
+        );
+        assert_eq!(&m[..mlen], msg);
+
+        // The message may overlap the signed message, in both directions
+        let mut buffer = [0u8; CRYPTO_BYTES + 7];
+        buffer[..msg.len()].copy_from_slice(msg);
+        let ptr = buffer.as_mut_ptr();
+        assert_eq!(
+            crypto_sign(ptr, &mut smlen, ptr, msg.len(), sk.as_ptr()),
+            SUCCESS
+        );
+        assert_eq!(buffer, sm);
+
+        assert_eq!(
+            crypto_sign_open(ptr, &mut mlen, ptr, smlen, pk.as_ptr()),
+            SUCCESS
+        );
+        assert_eq!(&buffer[..mlen], msg);
+
+        // The output is cleared on failure
+        sm[CRYPTO_BYTES] ^= 1;
+        assert_eq!(
+            crypto_sign_open(m.as_mut_ptr(), &mut mlen, sm.as_ptr(), smlen, pk.as_ptr()),
+            FAILURE
+        );
+        assert_eq!(mlen, usize::MAX);
+        assert_eq!(m, [0; CRYPTO_BYTES + 7]);
+
+        assert_eq!(
+            crypto_sign_open(m.as_mut_ptr(), &mut mlen, sm.as_ptr(), 1, pk.as_ptr()),
+            FAILURE
+        );
+    }
+}
+
+#[test]
+fn test_header() {
+    let header = include_str!(concat!(env!("OUT_DIR"), "/include/dilithium.h"));
+
+    for define in [
+        format!("#define CRYPTO_PUBLICKEYBYTES {CRYPTO_PUBLICKEYBYTES}\n"),
+        format!("#define CRYPTO_SECRETKEYBYTES {CRYPTO_SECRETKEYBYTES}\n"),
+        format!("#define CRYPTO_BYTES {CRYPTO_BYTES}\n"),
+    ] {
+        assert!(header.contains(&define));
+    }
+
+    for function in [
+        "crypto_sign_keypair(",
+        "crypto_sign_signature(",
+        "crypto_sign(",
+        "crypto_sign_verify(",
+        "crypto_sign_open(",
+    ] {
+        assert!(header.contains(&format!("int32_t {function}")));
+    }
+
+    assert!(header.contains("void dilithium_randombytes(uint8_t *out, size_t outlen);"));
+}
@@ -3,19 +3,19 @@ use core::{
     ops::{AddAssign, Mul, SubAssign},
 };
 
-pub fn pointwise_add_inplace<T: AddAssign, const N: usize>(lhs: &mut [T; N], rhs: [T; N]) {
+pub fn pointwise_add_inplace<T: AddAssign<U>, U>(lhs: &mut [T], rhs: impl IntoIterator<Item = U>) {
     for (lhs_coeff, rhs_coeff) in zip(lhs, rhs) {
         *lhs_coeff += rhs_coeff;
     }
 }
 
-pub fn pointwise_sub_inplace<T: SubAssign, const N: usize>(lhs: &mut [T; N], rhs: [T; N]) {
+pub fn pointwise_sub_inplace<T: SubAssign<U>, U>(lhs: &mut [T], rhs: impl IntoIterator<Item = U>) {
     for (lhs_coeff, rhs_coeff) in zip(lhs, rhs) {
         *lhs_coeff -= rhs_coeff;
     }
 }
 
-pub fn scalar_mul<'a, T, const N: usize>(lhs: &'a [T; N], rhs: &'a [T; N]) -> T
+pub fn scalar_mul<'a, T>(lhs: &'a [T], rhs: &'a [T]) -> T
 where
     T: Sum,
     &'a T: Mul<Output = T> + 'a,
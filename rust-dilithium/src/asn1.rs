@@ -0,0 +1,561 @@
+//! Encodings of the keys in X.509 `SubjectPublicKeyInfo` and PKCS#8 `OneAsymmetricKey` structures
+//!
+//! The ML-DSA keys of [`crate::mldsa`] are identified by the object identifiers that NIST
+//! registered, with absent parameters. Round-3 Dilithium has no NIST identifiers, so the keys of
+//! [`crate::signing`] are identified by the ones of the IBM arc that Open Quantum Safe uses, which
+//! tell the SHAKE and the AES variants apart.
+//!
+//! The public key is the bit string of the `SubjectPublicKeyInfo`. The private key is the choice
+//! of the IETF LAMPS profile for ML-DSA, which is used for round-3 Dilithium as well:
+//!
+//! ```text
+//! ML-DSA-PrivateKey ::= CHOICE {
+//!     seed [0] OCTET STRING (SIZE (32)),
+//!     expandedKey OCTET STRING,
+//!     both SEQUENCE {
+//!         seed OCTET STRING (SIZE (32)),
+//!         expandedKey OCTET STRING } }
+//! ```
+//!
+//! Decoding accepts the three forms, and checks that the expanded key is consistent and matches
+//! the seed and the public key when they are present. Encoding writes into a slice, without an
+//! allocator. With the `pem` feature, the keys also implement [`EncodePrivateKey`] and
+//! [`EncodePublicKey`], which allocate, along with PEM armor in both directions.
+//!
+//! [`EncodePrivateKey`]: pkcs8::EncodePrivateKey
+//! [`EncodePublicKey`]: spki::EncodePublicKey
+
+use const_oid::{AssociatedOid, ObjectIdentifier};
+use pkcs8::{
+    der::{
+        asn1::{AnyRef, BitStringRef, ContextSpecific, OctetStringRef},
+        Encode, Reader, SliceReader, SliceWriter, Tag, TagMode, TagNumber,
+    },
+    spki::{
+        AlgorithmIdentifierRef, AssociatedAlgorithmIdentifier, SignatureAlgorithmIdentifier,
+        SubjectPublicKeyInfoRef,
+    },
+    PrivateKeyInfo,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    counter::{Counter, ShakeCounter, SoftwareAesCounter},
+    mldsa, signing, CompactSecretKey, Dilithium2, Dilithium3, Dilithium5, Error, KeyPair, MlDsa44,
+    MlDsa65, MlDsa87, ParameterSet, PublicKey, SecretKey,
+};
+
+pub const ML_DSA_44: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.17");
+pub const ML_DSA_65: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.18");
+pub const ML_DSA_87: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.19");
+
+pub const DILITHIUM2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.7.4.4");
+pub const DILITHIUM3: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.7.6.5");
+pub const DILITHIUM5: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.7.8.7");
+pub const DILITHIUM2_AES: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.11.4.4");
+pub const DILITHIUM3_AES: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.11.6.5");
+pub const DILITHIUM5_AES: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.11.8.7");
+
+/// Upper bound on the size of an encoded `SubjectPublicKeyInfo`, with any parameter set
+pub const MAX_PUBLIC_KEY_DER_SIZE: usize = 2624;
+/// Upper bound on the size of an encoded `OneAsymmetricKey`, with any parameter set and form
+pub const MAX_PRIVATE_KEY_DER_SIZE: usize = 4992;
+/// Upper bound on the size of the encoded private key choice, with any parameter set and form
+const MAX_PRIVATE_KEY_CHOICE_SIZE: usize = 4944;
+
+/// Tag of the seed form of the private key choice
+const SEED_TAG_NUMBER: TagNumber = TagNumber::N0;
+const SEED_TAG: Tag = Tag::ContextSpecific {
+    constructed: false,
+    number: SEED_TAG_NUMBER,
+};
+
+/// Round-3 parameter set, with the object identifiers of its SHAKE and AES variants
+pub trait DilithiumOid: ParameterSet {
+    const SHAKE_OID: ObjectIdentifier;
+    const AES_OID: ObjectIdentifier;
+}
+
+/// ML-DSA parameter set, with its object identifier
+pub trait MlDsaOid: ParameterSet {
+    const OID: ObjectIdentifier;
+}
+
+/// Counter of the SHAKE or of the AES variant of round-3 Dilithium, which selects the object
+/// identifier of the keys bound to it
+pub trait CounterVariant: Counter {
+    const VARIANT: Variant;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Shake,
+    Aes,
+}
+
+/// Form of the private key in a `OneAsymmetricKey`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrivateKeyForm {
+    /// The 32-byte seed, which the key pair is derived from again when decoding
+    Seed,
+    /// The packed secret key
+    ExpandedKey,
+    /// Both the seed and the packed secret key
+    Both,
+}
+
+impl DilithiumOid for Dilithium2 {
+    const SHAKE_OID: ObjectIdentifier = DILITHIUM2;
+    const AES_OID: ObjectIdentifier = DILITHIUM2_AES;
+}
+
+impl DilithiumOid for Dilithium3 {
+    const SHAKE_OID: ObjectIdentifier = DILITHIUM3;
+    const AES_OID: ObjectIdentifier = DILITHIUM3_AES;
+}
+
+impl DilithiumOid for Dilithium5 {
+    const SHAKE_OID: ObjectIdentifier = DILITHIUM5;
+    const AES_OID: ObjectIdentifier = DILITHIUM5_AES;
+}
+
+impl MlDsaOid for MlDsa44 {
+    const OID: ObjectIdentifier = ML_DSA_44;
+}
+
+impl MlDsaOid for MlDsa65 {
+    const OID: ObjectIdentifier = ML_DSA_65;
+}
+
+impl MlDsaOid for MlDsa87 {
+    const OID: ObjectIdentifier = ML_DSA_87;
+}
+
+impl CounterVariant for ShakeCounter {
+    const VARIANT: Variant = Variant::Shake;
+}
+
+impl CounterVariant for SoftwareAesCounter {
+    const VARIANT: Variant = Variant::Aes;
+}
+
+const fn dilithium_oid<P: DilithiumOid, Ctr: CounterVariant>() -> ObjectIdentifier {
+    match Ctr::VARIANT {
+        Variant::Shake => P::SHAKE_OID,
+        Variant::Aes => P::AES_OID,
+    }
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> AssociatedOid for signing::SigningKey<P, Ctr> {
+    const OID: ObjectIdentifier = dilithium_oid::<P, Ctr>();
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> AssociatedOid for signing::VerifyingKey<P, Ctr> {
+    const OID: ObjectIdentifier = dilithium_oid::<P, Ctr>();
+}
+
+impl<P: MlDsaOid> AssociatedOid for mldsa::SigningKey<P> {
+    const OID: ObjectIdentifier = P::OID;
+}
+
+impl<P: MlDsaOid> AssociatedOid for mldsa::VerifyingKey<P> {
+    const OID: ObjectIdentifier = P::OID;
+}
+
+// The signatures are identified as the keys are, with absent parameters as well
+
+impl<P: DilithiumOid, Ctr: CounterVariant> AssociatedAlgorithmIdentifier
+    for signing::SigningKey<P, Ctr>
+{
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = algorithm_identifier::<Self>();
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> AssociatedAlgorithmIdentifier
+    for signing::VerifyingKey<P, Ctr>
+{
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = algorithm_identifier::<Self>();
+}
+
+impl<P: MlDsaOid> AssociatedAlgorithmIdentifier for mldsa::SigningKey<P> {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = algorithm_identifier::<Self>();
+}
+
+impl<P: MlDsaOid> AssociatedAlgorithmIdentifier for mldsa::VerifyingKey<P> {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = algorithm_identifier::<Self>();
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> SignatureAlgorithmIdentifier
+    for signing::SigningKey<P, Ctr>
+{
+    type Params = AnyRef<'static>;
+
+    const SIGNATURE_ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> =
+        algorithm_identifier::<Self>();
+}
+
+impl<P: MlDsaOid> SignatureAlgorithmIdentifier for mldsa::SigningKey<P> {
+    type Params = AnyRef<'static>;
+
+    const SIGNATURE_ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> =
+        algorithm_identifier::<Self>();
+}
+
+/// Signing key of round-3 Dilithium or of ML-DSA, which encode in the same way
+trait SigningKeyScheme: AssociatedOid + Sized {
+    type P: ParameterSet;
+
+    fn from_compact_secret_key(sk: &CompactSecretKey<Self::P>) -> Result<Self, Error>;
+    fn from_key_pair(key_pair: KeyPair<Self::P>) -> Self;
+    fn public_key_from_secret_key(sk: &SecretKey<Self::P>) -> Result<PublicKey<Self::P>, Error>;
+    fn key_pair(&self) -> &KeyPair<Self::P>;
+    fn compact_secret_key(&self) -> Option<&CompactSecretKey<Self::P>>;
+}
+
+/// Verifying key of round-3 Dilithium or of ML-DSA, which encode in the same way
+trait VerifyingKeyScheme: AssociatedOid + From<PublicKey<Self::P>> {
+    type P: ParameterSet;
+
+    fn public_key(&self) -> &PublicKey<Self::P>;
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> SigningKeyScheme for signing::SigningKey<P, Ctr> {
+    type P = P;
+
+    fn from_compact_secret_key(sk: &CompactSecretKey<P>) -> Result<Self, Error> {
+        Self::from_compact_secret_key(sk)
+    }
+
+    fn from_key_pair(key_pair: KeyPair<P>) -> Self {
+        Self::new(key_pair)
+    }
+
+    fn public_key_from_secret_key(sk: &SecretKey<P>) -> Result<PublicKey<P>, Error> {
+        crate::public_key_from_secret_key::<P, Ctr>(sk)
+    }
+
+    fn key_pair(&self) -> &KeyPair<P> {
+        self.key_pair()
+    }
+
+    fn compact_secret_key(&self) -> Option<&CompactSecretKey<P>> {
+        self.compact_secret_key()
+    }
+}
+
+impl<P: MlDsaOid> SigningKeyScheme for mldsa::SigningKey<P> {
+    type P = P;
+
+    fn from_compact_secret_key(sk: &CompactSecretKey<P>) -> Result<Self, Error> {
+        Self::from_compact_secret_key(sk)
+    }
+
+    fn from_key_pair(key_pair: KeyPair<P>) -> Self {
+        Self::new(key_pair)
+    }
+
+    fn public_key_from_secret_key(sk: &SecretKey<P>) -> Result<PublicKey<P>, Error> {
+        mldsa::public_key_from_secret_key::<P>(sk)
+    }
+
+    fn key_pair(&self) -> &KeyPair<P> {
+        self.key_pair()
+    }
+
+    fn compact_secret_key(&self) -> Option<&CompactSecretKey<P>> {
+        self.compact_secret_key()
+    }
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> VerifyingKeyScheme for signing::VerifyingKey<P, Ctr> {
+    type P = P;
+
+    fn public_key(&self) -> &PublicKey<P> {
+        self.public_key()
+    }
+}
+
+impl<P: MlDsaOid> VerifyingKeyScheme for mldsa::VerifyingKey<P> {
+    type P = P;
+
+    fn public_key(&self) -> &PublicKey<P> {
+        self.public_key()
+    }
+}
+
+const fn algorithm_identifier<K: AssociatedOid>() -> AlgorithmIdentifierRef<'static> {
+    AlgorithmIdentifierRef {
+        oid: K::OID,
+        parameters: None,
+    }
+}
+
+/// Writes the private key choice of `key` in `form` to `out`
+fn encode_private_key_choice<'o, K: SigningKeyScheme>(
+    key: &K,
+    form: PrivateKeyForm,
+    out: &'o mut [u8],
+) -> pkcs8::Result<&'o [u8]> {
+    let seed = || {
+        let sk = key.compact_secret_key().ok_or(pkcs8::Error::KeyMalformed)?;
+        Ok::<_, pkcs8::Error>(OctetStringRef::new(sk.as_bytes())?)
+    };
+    let expanded_key = OctetStringRef::new(key.key_pair().secret_key().as_bytes())?;
+    let mut writer = SliceWriter::new(out);
+
+    match form {
+        PrivateKeyForm::Seed => {
+            writer.context_specific(SEED_TAG_NUMBER, TagMode::Implicit, &seed()?)?
+        }
+        PrivateKeyForm::ExpandedKey => writer.encode(&expanded_key)?,
+        PrivateKeyForm::Both => {
+            let seed = seed()?;
+            let length = (seed.encoded_len()? + expanded_key.encoded_len()?)?;
+
+            writer.sequence(length, |writer| {
+                writer.encode(&seed)?;
+                writer.encode(&expanded_key)
+            })?
+        }
+    }
+
+    Ok(writer.finish()?)
+}
+
+fn encode_private_key<'o, K: SigningKeyScheme>(
+    key: &K,
+    form: PrivateKeyForm,
+    out: &'o mut [u8],
+) -> pkcs8::Result<&'o [u8]> {
+    let mut choice = Zeroizing::new([0u8; MAX_PRIVATE_KEY_CHOICE_SIZE]);
+    let private_key = encode_private_key_choice(key, form, choice.as_mut())?;
+
+    Ok(PrivateKeyInfo::new(algorithm_identifier::<K>(), private_key).encode_to_slice(out)?)
+}
+
+/// Derives the key pair of an expanded key, which fails if it is inconsistent
+fn from_expanded_key<K: SigningKeyScheme>(expanded_key: &[u8]) -> Result<K, Error> {
+    let sk = SecretKey::from_bytes(expanded_key)?;
+    let pk = K::public_key_from_secret_key(&sk)?;
+
+    Ok(K::from_key_pair(KeyPair::new(pk, sk)))
+}
+
+fn decode_private_key<K: SigningKeyScheme>(info: PrivateKeyInfo<'_>) -> pkcs8::Result<K> {
+    info.algorithm.assert_algorithm_oid(K::OID)?;
+
+    if info.algorithm.parameters.is_some() {
+        return Err(pkcs8::Error::ParametersMalformed);
+    }
+
+    let mut reader = SliceReader::new(info.private_key)?;
+    let key = match reader.peek_tag()? {
+        SEED_TAG => {
+            let seed = ContextSpecific::<OctetStringRef<'_>>::decode_implicit(
+                &mut reader,
+                SEED_TAG_NUMBER,
+            )?
+            .ok_or(pkcs8::Error::KeyMalformed)?
+            .value;
+
+            CompactSecretKey::from_bytes(seed.as_bytes())
+                .and_then(|sk| K::from_compact_secret_key(&sk))
+        }
+        Tag::OctetString => {
+            let expanded_key = reader.decode::<OctetStringRef<'_>>()?;
+
+            from_expanded_key::<K>(expanded_key.as_bytes())
+        }
+        Tag::Sequence => {
+            let (seed, expanded_key) = reader.sequence(|reader| {
+                Ok((
+                    reader.decode::<OctetStringRef<'_>>()?,
+                    reader.decode::<OctetStringRef<'_>>()?,
+                ))
+            })?;
+
+            CompactSecretKey::from_bytes(seed.as_bytes())
+                .and_then(|sk| K::from_compact_secret_key(&sk))
+                .and_then(|key| {
+                    if key.key_pair().secret_key().as_bytes() == expanded_key.as_bytes() {
+                        Ok(key)
+                    } else {
+                        Err(Error::KeyPairMismatch)
+                    }
+                })
+        }
+        tag => return Err(tag.unexpected_error(None).into()),
+    }
+    .map_err(|_| pkcs8::Error::KeyMalformed)?;
+    let key = reader.finish(key)?;
+
+    match info.public_key {
+        Some(pk) if pk != key.key_pair().public_key().as_bytes() => Err(pkcs8::Error::KeyMalformed),
+        _ => Ok(key),
+    }
+}
+
+fn encode_public_key<'o, K: VerifyingKeyScheme>(
+    key: &K,
+    out: &'o mut [u8],
+) -> spki::Result<&'o [u8]> {
+    let spki = SubjectPublicKeyInfoRef {
+        algorithm: algorithm_identifier::<K>(),
+        subject_public_key: BitStringRef::from_bytes(key.public_key().as_bytes())?,
+    };
+
+    Ok(spki.encode_to_slice(out)?)
+}
+
+fn decode_public_key<K: VerifyingKeyScheme>(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<K> {
+    spki.algorithm.assert_algorithm_oid(K::OID)?;
+
+    if spki.algorithm.parameters.is_some() {
+        return Err(spki::Error::KeyMalformed);
+    }
+
+    spki.subject_public_key
+        .as_bytes()
+        .ok_or(spki::Error::KeyMalformed)
+        .and_then(|pk| PublicKey::from_bytes(pk).map_err(|_| spki::Error::KeyMalformed))
+        .map(K::from)
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> signing::SigningKey<P, Ctr> {
+    /// Writes the `OneAsymmetricKey` of the key in `form` to `out`. Fails if `out` is too small,
+    /// or if `form` needs the seed and the key was bound without it.
+    pub fn encode_pkcs8_der<'o>(
+        &self,
+        form: PrivateKeyForm,
+        out: &'o mut [u8],
+    ) -> pkcs8::Result<&'o [u8]> {
+        encode_private_key(self, form, out)
+    }
+}
+
+impl<P: MlDsaOid> mldsa::SigningKey<P> {
+    /// Writes the `OneAsymmetricKey` of the key in `form` to `out`. Fails if `out` is too small,
+    /// or if `form` needs the seed and the key was made without it.
+    pub fn encode_pkcs8_der<'o>(
+        &self,
+        form: PrivateKeyForm,
+        out: &'o mut [u8],
+    ) -> pkcs8::Result<&'o [u8]> {
+        encode_private_key(self, form, out)
+    }
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> signing::VerifyingKey<P, Ctr> {
+    /// Writes the `SubjectPublicKeyInfo` of the key to `out`, which fails if it is too small
+    pub fn encode_public_key_der<'o>(&self, out: &'o mut [u8]) -> spki::Result<&'o [u8]> {
+        encode_public_key(self, out)
+    }
+}
+
+impl<P: MlDsaOid> mldsa::VerifyingKey<P> {
+    /// Writes the `SubjectPublicKeyInfo` of the key to `out`, which fails if it is too small
+    pub fn encode_public_key_der<'o>(&self, out: &'o mut [u8]) -> spki::Result<&'o [u8]> {
+        encode_public_key(self, out)
+    }
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> TryFrom<PrivateKeyInfo<'_>>
+    for signing::SigningKey<P, Ctr>
+{
+    type Error = pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> pkcs8::Result<Self> {
+        decode_private_key(info)
+    }
+}
+
+impl<P: MlDsaOid> TryFrom<PrivateKeyInfo<'_>> for mldsa::SigningKey<P> {
+    type Error = pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> pkcs8::Result<Self> {
+        decode_private_key(info)
+    }
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> TryFrom<SubjectPublicKeyInfoRef<'_>>
+    for signing::VerifyingKey<P, Ctr>
+{
+    type Error = spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        decode_public_key(spki)
+    }
+}
+
+impl<P: MlDsaOid> TryFrom<SubjectPublicKeyInfoRef<'_>> for mldsa::VerifyingKey<P> {
+    type Error = spki::Error;
+
+    fn try_from(spki: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        decode_public_key(spki)
+    }
+}
+
+#[cfg(feature = "pem")]
+mod alloc {
+    use pkcs8::{der::SecretDocument, EncodePrivateKey};
+    use spki::{der::Document, EncodePublicKey};
+
+    use super::*;
+
+    /// The seed form when the key knows its seed, and the expanded form otherwise
+    fn default_form<K: SigningKeyScheme>(key: &K) -> PrivateKeyForm {
+        match key.compact_secret_key() {
+            Some(_) => PrivateKeyForm::Seed,
+            None => PrivateKeyForm::ExpandedKey,
+        }
+    }
+
+    fn to_secret_document<K: SigningKeyScheme>(key: &K) -> pkcs8::Result<SecretDocument> {
+        let mut der = Zeroizing::new([0u8; MAX_PRIVATE_KEY_DER_SIZE]);
+        let der = encode_private_key(key, default_form(key), der.as_mut())?;
+
+        Ok(SecretDocument::try_from(der)?)
+    }
+
+    fn to_document<K: VerifyingKeyScheme>(key: &K) -> spki::Result<Document> {
+        let mut der = [0u8; MAX_PUBLIC_KEY_DER_SIZE];
+
+        Ok(Document::try_from(encode_public_key(key, &mut der)?)?)
+    }
+
+    /// Encodes the seed form when the key knows its seed, and the expanded form otherwise
+    impl<P: DilithiumOid, Ctr: CounterVariant> EncodePrivateKey for signing::SigningKey<P, Ctr> {
+        fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+            to_secret_document(self)
+        }
+    }
+
+    /// Encodes the seed form when the key knows its seed, and the expanded form otherwise
+    impl<P: MlDsaOid> EncodePrivateKey for mldsa::SigningKey<P> {
+        fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+            to_secret_document(self)
+        }
+    }
+
+    impl<P: DilithiumOid, Ctr: CounterVariant> EncodePublicKey for signing::VerifyingKey<P, Ctr> {
+        fn to_public_key_der(&self) -> spki::Result<Document> {
+            to_document(self)
+        }
+    }
+
+    impl<P: MlDsaOid> EncodePublicKey for mldsa::VerifyingKey<P> {
+        fn to_public_key_der(&self) -> spki::Result<Document> {
+            to_document(self)
+        }
+    }
+}
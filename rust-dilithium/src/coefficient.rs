@@ -1,5 +1,5 @@
-use crate::{D, GAMMA2, Q, Q_MOD_2POW32_INVERSE};
-use core::num::Wrapping;
+use crate::{params::ParameterSet, D, Q, Q_MOD_2POW32_INVERSE};
+use core::{hint::black_box, num::Wrapping};
 
 pub type Coefficient = i32;
 
@@ -16,19 +16,19 @@ pub fn reduce_32(n: Coefficient) -> Coefficient {
 }
 
 pub fn caddq(n: Coefficient) -> Coefficient {
-    if n >= 0 {
-        n
-    } else {
-        n + Q
-    }
+    n + (value_barrier(n >> 31) & Q)
 }
 
-pub fn decompose(n: Coefficient) -> (Coefficient, Coefficient) {
+pub fn decompose<P: ParameterSet>(n: Coefficient) -> (Coefficient, Coefficient) {
     let n1 = (n + 127) >> 7;
-    let mut n1 = (n1 * 1025 + (1 << 21)) >> 22;
-    n1 &= 15;
+    let n1 = if P::GAMMA2 == (Q - 1) / 32 {
+        ((n1 * 1025 + (1 << 21)) >> 22) & 15
+    } else {
+        let n1 = (n1 * 11275 + (1 << 23)) >> 24;
+        n1 ^ (((43 - n1) >> 31) & n1)
+    };
 
-    let mut n0 = n - n1 * 2 * GAMMA2;
+    let mut n0 = n - n1 * 2 * P::GAMMA2;
     n0 -= (((Q - 1) / 2 - n0) >> 31) & Q;
 
     (n0, n1)
@@ -42,3 +42,28 @@ pub fn power2round(coeff: Coefficient) -> (Coefficient, Coefficient) {
 pub fn abs(n: Coefficient) -> Coefficient {
     n - ((n >> 31) & (2 * n))
 }
+
+/// Assumes that the difference of the operands does not overflow, which holds for coefficients
+/// smaller than `Q` in absolute value
+pub fn max(lhs: Coefficient, rhs: Coefficient) -> Coefficient {
+    lhs ^ ((lhs ^ rhs) & value_barrier((lhs - rhs) >> 31))
+}
+
+/// Returns 1 if the high bits of `n1 + n0` differ from `n1`, and 0 otherwise
+pub fn make_hint<P: ParameterSet>(n0: Coefficient, n1: Coefficient) -> Coefficient {
+    let shifted_n0 = n0 + P::GAMMA2;
+
+    // Each of these masks is -1 if the condition holds and 0 otherwise
+    let is_above = (P::GAMMA2 - n0) >> 31;
+    let is_below = shifted_n0 >> 31;
+    let is_lower_bound = !((shifted_n0 | -shifted_n0) >> 31);
+    let is_n1_nonzero = (n1 | -n1) >> 31;
+
+    value_barrier(is_above | is_below | (is_lower_bound & is_n1_nonzero)) & 1
+}
+
+/// Hides a mask derived from secret data from the optimizer, which would otherwise recognize
+/// comparisons in the branch-free code and may compile them back into branches
+fn value_barrier(mask: Coefficient) -> Coefficient {
+    black_box(mask)
+}
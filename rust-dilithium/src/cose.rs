@@ -0,0 +1,538 @@
+//! `COSE_Sign1` envelopes and `COSE_Key` encodings of ML-DSA and round-3 Dilithium public keys
+//!
+//! The ML-DSA algorithms are identified by the values that IANA registered, and the keys use the
+//! algorithm key pair (AKP) key type, as in the IETF COSE draft for ML-DSA. The context string of
+//! ML-DSA is empty. Round-3 Dilithium has no registered identifiers, so its envelopes and keys use
+//! identifiers that the caller picks from the private-use range through [`DilithiumCoseAlgorithm`],
+//! and carry the signatures of [`crate::sign`].
+//!
+//! The structures are encoded in deterministic CBOR into slices, so no allocator is needed, and
+//! their sizes are known from the sizes of the signature and of the public key, see
+//! [`sign1_size`] and [`key_size`]. Decoding accepts the tagged and the untagged `COSE_Sign1`,
+//! requires the algorithm in the protected header, and rejects critical headers as it understands
+//! none. Detached payloads are not supported.
+
+use crate::{
+    counter::Counter,
+    envelope::{Dilithium, MlDsa, Scheme},
+    Error, MlDsa44, MlDsa65, MlDsa87, ParameterSet, PublicKey, SecretKey, Signature,
+    RANDOMNESS_SIZE,
+};
+
+pub const ML_DSA_44: i64 = -48;
+pub const ML_DSA_65: i64 = -49;
+pub const ML_DSA_87: i64 = -50;
+
+/// Key type of algorithm key pairs, which carry the algorithm along with the key
+pub const KEY_TYPE_AKP: i64 = 7;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+
+const SIGN1_TAG: u64 = 18;
+
+const HEADER_ALGORITHM: i64 = 1;
+const HEADER_CRITICAL: i64 = 2;
+
+const KEY_TYPE: i64 = 1;
+const KEY_ALGORITHM: i64 = 3;
+const KEY_PUBLIC: i64 = -1;
+
+/// Beginning of the `Sig_structure`, an array of four items and its `Signature1` context
+const SIG_STRUCTURE_PREFIX: &[u8] = b"\x84\x6aSignature1";
+
+/// Size of the largest head, which has an 8-byte argument
+const HEAD_SIZE_MAX: usize = 9;
+/// Depth of nesting above which the headers are rejected
+const DEPTH_MAX: usize = 8;
+
+/// Signature algorithm of `COSE_Sign1` envelopes and of `COSE_Key` keys, with its COSE algorithm
+/// identifier
+///
+/// It is implemented by the ML-DSA parameter sets, with their registered identifiers, and by the
+/// implementations of [`DilithiumCoseAlgorithm`].
+pub trait CoseAlgorithm {
+    type ParameterSet: ParameterSet;
+    /// Scheme that signs the `Sig_structure`, which only this crate provides
+    type Scheme: Scheme<ParameterSet = Self::ParameterSet>;
+
+    const ALGORITHM: i64;
+}
+
+/// Round-3 Dilithium parameter set and counter, under a COSE algorithm identifier picked by the
+/// caller
+///
+/// No identifier is registered for round-3 Dilithium, so it should be taken from the private-use
+/// range, below -65536. The `Sig_structure` is signed as a message by [`crate::sign`], or by
+/// [`crate::sign_hedged`] in the hedged variant, and verified by [`crate::verify`].
+pub trait DilithiumCoseAlgorithm {
+    type ParameterSet: ParameterSet;
+    type Counter: Counter;
+
+    const ALGORITHM: i64;
+}
+
+impl CoseAlgorithm for MlDsa44 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: i64 = ML_DSA_44;
+}
+
+impl CoseAlgorithm for MlDsa65 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: i64 = ML_DSA_65;
+}
+
+impl CoseAlgorithm for MlDsa87 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: i64 = ML_DSA_87;
+}
+
+impl<A: DilithiumCoseAlgorithm> CoseAlgorithm for A {
+    type ParameterSet = A::ParameterSet;
+    type Scheme = Dilithium<A::ParameterSet, A::Counter>;
+
+    const ALGORITHM: i64 = A::ALGORITHM;
+}
+
+/// Returns the size of the `COSE_Sign1` of a payload of `payload_size` bytes
+pub const fn sign1_size<A: CoseAlgorithm>(payload_size: usize) -> usize {
+    head_size(SIGN1_TAG)
+        + head_size(4)
+        + bytes_size(protected_size::<A>())
+        + head_size(0)
+        + bytes_size(payload_size)
+        + bytes_size(<A::ParameterSet as ParameterSet>::SIGNATURE_SIZE)
+}
+
+/// Returns the size of the `COSE_Key` of a public key
+pub const fn key_size<A: CoseAlgorithm>() -> usize {
+    head_size(3)
+        + int_size(KEY_TYPE)
+        + int_size(KEY_TYPE_AKP)
+        + int_size(KEY_ALGORITHM)
+        + int_size(A::ALGORITHM)
+        + int_size(KEY_PUBLIC)
+        + bytes_size(<A::ParameterSet as ParameterSet>::PUBLIC_KEY_SIZE)
+}
+
+/// Signs `payload` along with `external_aad` in the hedged variant, where `rnd` must be fresh
+/// randomness, and writes the tagged `COSE_Sign1` to `out`
+///
+/// Fails with [`Error::BufferTooSmall`] if `out` is smaller than [`sign1_size`].
+pub fn sign1<'o, A: CoseAlgorithm>(
+    payload: &[u8],
+    external_aad: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+    out: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    sign1_with::<A>(payload, external_aad, sk, Some(rnd), out)
+}
+
+/// Signs `payload` as [`sign1`] does in the deterministic variant
+pub fn sign1_deterministic<'o, A: CoseAlgorithm>(
+    payload: &[u8],
+    external_aad: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    out: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    sign1_with::<A>(payload, external_aad, sk, None, out)
+}
+
+/// Verifies `sign1` along with `external_aad`, and returns its payload
+///
+/// Fails with [`Error::MalformedEncoding`] if `sign1` is not a well-formed `COSE_Sign1` with an
+/// attached payload, and with [`Error::AlgorithmMismatch`] if its protected header does not name
+/// the algorithm of `A`.
+pub fn verify1<'a, A: CoseAlgorithm>(
+    sign1: &'a [u8],
+    external_aad: &[u8],
+    pk: &PublicKey<A::ParameterSet>,
+) -> Result<&'a [u8], Error> {
+    let mut reader = Reader::new(sign1);
+    let mut head = reader.head()?;
+
+    if head == (TAG, SIGN1_TAG) {
+        head = reader.head()?;
+    }
+
+    if head != (ARRAY, 4) {
+        return Err(Error::MalformedEncoding);
+    }
+
+    let protected = reader.bytes()?;
+
+    // The unprotected header is skipped as a whole, which bounds its number of entries
+    if reader.peek_major()? != MAP {
+        return Err(Error::MalformedEncoding);
+    }
+
+    reader.skip(0)?;
+
+    let payload = reader.bytes()?;
+    let signature = reader.bytes()?;
+    reader.finish()?;
+
+    check_protected::<A>(protected)?;
+
+    let signature = Signature::<A::ParameterSet>::from_bytes(signature)?;
+    sig_structure(protected, external_aad, payload, |parts| {
+        A::Scheme::verify_parts(parts, &signature, pk)
+    })?;
+
+    Ok(payload)
+}
+
+/// Writes the `COSE_Key` of `pk` to `out`
+///
+/// Fails with [`Error::BufferTooSmall`] if `out` is smaller than [`key_size`].
+pub fn encode_key<'o, A: CoseAlgorithm>(
+    pk: &PublicKey<A::ParameterSet>,
+    out: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    let mut writer = Writer::new(out, key_size::<A>())?;
+
+    // The labels are sorted by their encodings, as deterministic CBOR requires
+    writer.head(MAP, 3);
+    writer.int(KEY_TYPE);
+    writer.int(KEY_TYPE_AKP);
+    writer.int(KEY_ALGORITHM);
+    writer.int(A::ALGORITHM);
+    writer.int(KEY_PUBLIC);
+    writer.bytes(pk.as_bytes());
+
+    Ok(writer.finish())
+}
+
+/// Decodes the public key of the `COSE_Key` `key`, ignoring the parameters other than its key
+/// type, its algorithm and its public key
+///
+/// Fails with [`Error::AlgorithmMismatch`] if the key is not an AKP key of the algorithm of `A`.
+pub fn decode_key<A: CoseAlgorithm>(key: &[u8]) -> Result<PublicKey<A::ParameterSet>, Error> {
+    let mut reader = Reader::new(key);
+    let (mut key_type, mut algorithm, mut pk) = (None, None, None);
+
+    for _ in 0..reader.map()? {
+        match reader.label()? {
+            Some(KEY_TYPE) => key_type = Some(reader.int()?),
+            Some(KEY_ALGORITHM) => algorithm = Some(reader.int()?),
+            Some(KEY_PUBLIC) => pk = Some(reader.bytes()?),
+            _ => reader.skip(0)?,
+        }
+    }
+
+    reader.finish()?;
+
+    if key_type != Some(KEY_TYPE_AKP) || algorithm != Some(A::ALGORITHM) {
+        return Err(Error::AlgorithmMismatch);
+    }
+
+    PublicKey::from_bytes(pk.ok_or(Error::MalformedEncoding)?)
+}
+
+/// Signs `payload` as [`sign1`] does, in the deterministic variant when `rnd` is `None`
+fn sign1_with<'o, A: CoseAlgorithm>(
+    payload: &[u8],
+    external_aad: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+    out: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    let mut writer = Writer::new(out, sign1_size::<A>(payload.len()))?;
+    let mut protected = [0; 2 + HEAD_SIZE_MAX];
+    let protected = encode_protected::<A>(&mut protected);
+    let signature = sig_structure(protected, external_aad, payload, |parts| {
+        A::Scheme::sign_parts(parts, sk, rnd)
+    })?;
+
+    writer.head(TAG, SIGN1_TAG);
+    writer.head(ARRAY, 4);
+    writer.bytes(protected);
+    writer.head(MAP, 0);
+    writer.bytes(payload);
+    writer.bytes(signature.as_bytes());
+
+    Ok(writer.finish())
+}
+
+/// Encodes the protected header of `A` into `buffer`, which only holds the algorithm
+fn encode_protected<A: CoseAlgorithm>(buffer: &mut [u8]) -> &[u8] {
+    let mut writer = Writer {
+        out: buffer,
+        len: 0,
+    };
+
+    writer.head(MAP, 1);
+    writer.int(HEADER_ALGORITHM);
+    writer.int(A::ALGORITHM);
+
+    writer.finish()
+}
+
+/// Checks that `protected` names the algorithm of `A`, and no critical header
+fn check_protected<A: CoseAlgorithm>(protected: &[u8]) -> Result<(), Error> {
+    // An empty protected header stands for an empty map
+    if protected.is_empty() {
+        return Err(Error::AlgorithmMismatch);
+    }
+
+    let mut reader = Reader::new(protected);
+    let mut algorithm = None;
+
+    for _ in 0..reader.map()? {
+        match reader.label()? {
+            Some(HEADER_ALGORITHM) if algorithm.is_none() => algorithm = Some(reader.int()?),
+            Some(HEADER_ALGORITHM | HEADER_CRITICAL) => return Err(Error::MalformedEncoding),
+            _ => reader.skip(0)?,
+        }
+    }
+
+    reader.finish()?;
+
+    match algorithm {
+        Some(algorithm) if algorithm == A::ALGORITHM => Ok(()),
+        _ => Err(Error::AlgorithmMismatch),
+    }
+}
+
+/// Passes the parts of the `Sig_structure` of `payload` to `f`, which signs or verifies their
+/// concatenation
+fn sig_structure<T>(
+    protected: &[u8],
+    external_aad: &[u8],
+    payload: &[u8],
+    f: impl FnOnce(&[&[u8]]) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let heads =
+        [protected, external_aad, payload].map(|bytes| Head::new(BYTES, bytes.len() as u64));
+
+    f(&[
+        SIG_STRUCTURE_PREFIX,
+        heads[0].as_bytes(),
+        protected,
+        heads[1].as_bytes(),
+        external_aad,
+        heads[2].as_bytes(),
+        payload,
+    ])
+}
+
+const fn head_size(argument: u64) -> usize {
+    match argument {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+const fn int_size(value: i64) -> usize {
+    head_size(int_argument(value))
+}
+
+const fn bytes_size(size: usize) -> usize {
+    head_size(size as u64) + size
+}
+
+const fn protected_size<A: CoseAlgorithm>() -> usize {
+    head_size(1) + int_size(HEADER_ALGORITHM) + int_size(A::ALGORITHM)
+}
+
+/// Returns the argument of the head of `value`, which is `-1 - value` for negative integers
+const fn int_argument(value: i64) -> u64 {
+    if value < 0 {
+        !value as u64
+    } else {
+        value as u64
+    }
+}
+
+/// Initial byte of a data item, followed by its argument in the shortest form
+struct Head {
+    bytes: [u8; HEAD_SIZE_MAX],
+    len: usize,
+}
+
+impl Head {
+    fn new(major: u8, argument: u64) -> Self {
+        let len = head_size(argument);
+        let mut bytes = [0; HEAD_SIZE_MAX];
+
+        if len == 1 {
+            bytes[0] = major << 5 | argument as u8;
+        } else {
+            // The additional information 24 to 27 announces arguments of 1 to 8 bytes
+            bytes[0] = major << 5 | (24 + (len - 1).trailing_zeros()) as u8;
+            bytes[1..len].copy_from_slice(&argument.to_be_bytes()[HEAD_SIZE_MAX - len..]);
+        }
+
+        Self { bytes, len }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Writer into a buffer whose size was checked beforehand
+struct Writer<'o> {
+    out: &'o mut [u8],
+    len: usize,
+}
+
+impl<'o> Writer<'o> {
+    fn new(out: &'o mut [u8], needed: usize) -> Result<Self, Error> {
+        match out.len() {
+            actual if actual < needed => Err(Error::BufferTooSmall { needed, actual }),
+            _ => Ok(Self { out, len: 0 }),
+        }
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        self.out[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn head(&mut self, major: u8, argument: u64) {
+        self.raw(Head::new(major, argument).as_bytes());
+    }
+
+    fn int(&mut self, value: i64) {
+        let major = if value < 0 { NEGATIVE } else { UNSIGNED };
+        self.head(major, int_argument(value));
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.head(BYTES, bytes.len() as u64);
+        self.raw(bytes);
+    }
+
+    fn finish(self) -> &'o [u8] {
+        &self.out[..self.len]
+    }
+}
+
+/// Reader of definite-length data items, which fails with [`Error::MalformedEncoding`]
+struct Reader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    fn take(&mut self, len: u64) -> Result<&'a [u8], Error> {
+        let len = usize::try_from(len).map_err(|_| Error::MalformedEncoding)?;
+
+        if len > self.input.len() {
+            return Err(Error::MalformedEncoding);
+        }
+
+        let (retval, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(retval)
+    }
+
+    fn peek_major(&self) -> Result<u8, Error> {
+        self.input
+            .first()
+            .map(|initial| initial >> 5)
+            .ok_or(Error::MalformedEncoding)
+    }
+
+    /// Returns the major type and the argument of the next data item
+    fn head(&mut self) -> Result<(u8, u64), Error> {
+        let initial = self.take(1)?[0];
+        let argument = match initial & 0x1f {
+            additional @ 0..=23 => additional.into(),
+            additional @ 24..=27 => {
+                let mut argument = [0; 8];
+                let len = 1 << (additional - 24);
+                argument[8 - len..].copy_from_slice(self.take(len as u64)?);
+
+                u64::from_be_bytes(argument)
+            }
+            // Reserved values and indefinite lengths
+            _ => return Err(Error::MalformedEncoding),
+        };
+
+        Ok((initial >> 5, argument))
+    }
+
+    fn int(&mut self) -> Result<i64, Error> {
+        let (major, argument) = self.head()?;
+        let argument = i64::try_from(argument).map_err(|_| Error::MalformedEncoding)?;
+
+        match major {
+            UNSIGNED => Ok(argument),
+            NEGATIVE => Ok(-1 - argument),
+            _ => Err(Error::MalformedEncoding),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        match self.head()? {
+            (BYTES, len) => self.take(len),
+            _ => Err(Error::MalformedEncoding),
+        }
+    }
+
+    /// Returns the number of entries of a map
+    fn map(&mut self) -> Result<u64, Error> {
+        match self.head()? {
+            (MAP, entries) => Ok(entries),
+            _ => Err(Error::MalformedEncoding),
+        }
+    }
+
+    /// Returns an integer label, or skips a label of another type
+    fn label(&mut self) -> Result<Option<i64>, Error> {
+        match self.peek_major()? {
+            UNSIGNED | NEGATIVE => self.int().map(Some),
+            _ => self.skip(0).map(|_| None),
+        }
+    }
+
+    /// Skips a data item nested at `depth`
+    fn skip(&mut self, depth: usize) -> Result<(), Error> {
+        if depth > DEPTH_MAX {
+            return Err(Error::MalformedEncoding);
+        }
+
+        let (major, argument) = self.head()?;
+        let items = match major {
+            BYTES | 3 => return self.take(argument).map(|_| ()),
+            ARRAY => argument,
+            MAP => argument.checked_mul(2).ok_or(Error::MalformedEncoding)?,
+            TAG => 1,
+            // Integers and simple values are made of their head
+            _ => 0,
+        };
+
+        for _ in 0..items {
+            self.skip(depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        match self.input {
+            [] => Ok(()),
+            _ => Err(Error::MalformedEncoding),
+        }
+    }
+}
@@ -4,12 +4,27 @@ use aes::{
     cipher::{BlockEncrypt, KeyInit},
     Aes256Enc,
 };
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake128, Shake128Reader, Shake256, Shake256Reader,
+};
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{subarr, subarray::Subarray, SEED_SIZE};
 
 pub const KEY_SIZE: usize = 32;
 pub const BLOCK_SIZE: usize = 16;
 
-pub trait Counter {
+pub trait Counter: Sized {
     fn new(key: &[u8; KEY_SIZE]) -> Self;
+
+    /// Creates the counter used to sample the secret and masking vectors from the whole
+    /// `rho_prime` seed. The AES variant only uses its first half as a key.
+    fn new_wide(seed: &[u8; SEED_SIZE]) -> Self {
+        Self::new(subarr!(seed[..KEY_SIZE]))
+    }
+
     fn reset(&mut self, nonce: u16);
     fn squeeze<const N: usize>(&mut self) -> [u8; N];
 }
@@ -60,3 +75,96 @@ impl Counter for SoftwareAesCounter {
         retval
     }
 }
+
+// The key schedule of the encryptor wipes itself when dropped, so it is wiped by replacing it with
+// the schedule of the zero key
+impl Zeroize for SoftwareAesCounter {
+    fn zeroize(&mut self) {
+        self.encryptor = Aes256Enc::new(From::from(&[0; KEY_SIZE]));
+        self.iv.zeroize();
+        self.counter.zeroize();
+        self.buf.zeroize();
+        self.i = BLOCK_SIZE;
+    }
+}
+
+// The buffer holds output of the stream
+impl Drop for SoftwareAesCounter {
+    fn drop(&mut self) {
+        self.iv.zeroize();
+        self.buf.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SoftwareAesCounter {}
+
+/// Stream of the standard (non-AES) Dilithium variant
+///
+/// The public matrix is sampled from SHAKE128 and the secret and masking vectors from SHAKE256.
+/// In both cases, the stream is the output of the XOF applied to the seed followed by the nonce
+/// in little-endian order.
+pub struct ShakeCounter {
+    seed: [u8; SEED_SIZE],
+    seed_size: usize,
+    reader: ShakeReader,
+}
+
+enum ShakeReader {
+    Shake128(Shake128Reader),
+    Shake256(Shake256Reader),
+}
+
+impl Counter for ShakeCounter {
+    fn new(key: &[u8; KEY_SIZE]) -> Self {
+        let mut seed = [0; SEED_SIZE];
+        seed[..KEY_SIZE].copy_from_slice(key);
+
+        Self {
+            seed,
+            seed_size: KEY_SIZE,
+            reader: ShakeReader::Shake128(Shake128::default().chain(key).finalize_xof()),
+        }
+    }
+
+    fn new_wide(seed: &[u8; SEED_SIZE]) -> Self {
+        Self {
+            seed: *seed,
+            seed_size: SEED_SIZE,
+            reader: ShakeReader::Shake256(Shake256::default().chain(seed).finalize_xof()),
+        }
+    }
+
+    fn reset(&mut self, nonce: u16) {
+        let seed = &self.seed[..self.seed_size];
+        let nonce = nonce.to_le_bytes();
+
+        self.reader = match self.reader {
+            ShakeReader::Shake128(_) => {
+                ShakeReader::Shake128(Shake128::default().chain(seed).chain(nonce).finalize_xof())
+            }
+            ShakeReader::Shake256(_) => {
+                ShakeReader::Shake256(Shake256::default().chain(seed).chain(nonce).finalize_xof())
+            }
+        };
+    }
+
+    fn squeeze<const N: usize>(&mut self) -> [u8; N] {
+        let mut retval = [0; N];
+
+        match &mut self.reader {
+            ShakeReader::Shake128(reader) => reader.read(&mut retval),
+            ShakeReader::Shake256(reader) => reader.read(&mut retval),
+        }
+
+        retval
+    }
+}
+
+// The sponge states of the readers wipe themselves
+impl Drop for ShakeCounter {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for ShakeCounter {}
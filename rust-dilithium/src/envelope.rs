@@ -0,0 +1,75 @@
+//! Signature schemes of the envelopes of [`crate::cose`] and [`crate::jose`], which sign the
+//! concatenation of the parts that the envelopes put around their payloads
+//!
+//! The traits of the algorithms of both modules name one of the schemes below. They are public in
+//! this private module so that the algorithms can name them, but cannot be used from outside.
+
+use core::marker::PhantomData;
+
+use crate::{
+    counter::Counter, mldsa, Error, ParameterSet, PublicKey, SecretKey, Signature, RANDOMNESS_SIZE,
+};
+
+pub trait Scheme {
+    type ParameterSet: ParameterSet;
+
+    /// Signs the concatenation of `parts` in the hedged variant, or in the deterministic one when
+    /// `rnd` is `None`
+    fn sign_parts(
+        parts: &[&[u8]],
+        sk: &SecretKey<Self::ParameterSet>,
+        rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+    ) -> Result<Signature<Self::ParameterSet>, Error>;
+
+    fn verify_parts(
+        parts: &[&[u8]],
+        signature: &Signature<Self::ParameterSet>,
+        pk: &PublicKey<Self::ParameterSet>,
+    ) -> Result<(), Error>;
+}
+
+/// ML-DSA with an empty context string, see [`crate::mldsa::sign`]
+pub struct MlDsa<P>(PhantomData<P>);
+
+/// Round-3 Dilithium with the counter `Ctr`, see [`crate::sign`] and [`crate::sign_hedged`]
+pub struct Dilithium<P, Ctr>(PhantomData<(P, Ctr)>);
+
+impl<P: ParameterSet> Scheme for MlDsa<P> {
+    type ParameterSet = P;
+
+    fn sign_parts(
+        parts: &[&[u8]],
+        sk: &SecretKey<P>,
+        rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+    ) -> Result<Signature<P>, Error> {
+        mldsa::sign_parts::<P>(parts, &[], sk, rnd.unwrap_or(&[0; RANDOMNESS_SIZE]))
+    }
+
+    fn verify_parts(
+        parts: &[&[u8]],
+        signature: &Signature<P>,
+        pk: &PublicKey<P>,
+    ) -> Result<(), Error> {
+        mldsa::verify_parts::<P>(parts, &[], signature, pk)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Scheme for Dilithium<P, Ctr> {
+    type ParameterSet = P;
+
+    fn sign_parts(
+        parts: &[&[u8]],
+        sk: &SecretKey<P>,
+        rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+    ) -> Result<Signature<P>, Error> {
+        crate::sign_parts::<P, Ctr>(parts, sk, rnd)
+    }
+
+    fn verify_parts(
+        parts: &[&[u8]],
+        signature: &Signature<P>,
+        pk: &PublicKey<P>,
+    ) -> Result<(), Error> {
+        crate::verify_parts::<P, Ctr>(parts, signature, pk)
+    }
+}
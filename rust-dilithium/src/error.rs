@@ -0,0 +1,60 @@
+use core::fmt::{self, Display, Formatter};
+
+/// Reason why an operation of the crate failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The signature is not encoded as a signature of the parameter set
+    MalformedSignature,
+    /// The accumulated numbers of ones of the hint are decreasing or larger than `OMEGA`, the
+    /// positions of the ones are not strictly increasing in a polynomial, or the unused positions
+    /// are not zeroed
+    InvalidHintEncoding,
+    /// A coefficient of the response vector `z` is too large
+    NormBoundExceeded,
+    /// The signature was decoded but does not match the message and the public key
+    ChallengeMismatch,
+    /// A key or a signature was given as a slice of the wrong size
+    WrongLength { expected: usize, actual: usize },
+    /// The context string is longer than `mldsa::CONTEXT_SIZE_MAX`
+    ContextTooLong,
+    /// A coefficient of the secret vectors `s1` or `s2` is larger than `ETA`
+    SecretKeyOutOfRange,
+    /// The `t0` or the `tr` stored in the secret key were not derived from its secret vectors
+    InconsistentSecretKey,
+    /// The public key is not the one of the secret key
+    KeyPairMismatch,
+    /// A coefficient of an expanded key blob is not reduced modulo `Q`
+    InvalidExpandedKey,
+    /// The output buffer is smaller than the `needed` bytes
+    BufferTooSmall { needed: usize, actual: usize },
+    /// A COSE or JOSE structure could not be decoded
+    MalformedEncoding,
+    /// The algorithm named by a COSE or JOSE structure is not the one of the parameter set
+    AlgorithmMismatch,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedSignature => f.write_str("malformed signature"),
+            Self::InvalidHintEncoding => f.write_str("invalid hint encoding"),
+            Self::NormBoundExceeded => f.write_str("norm bound exceeded"),
+            Self::ChallengeMismatch => f.write_str("challenge mismatch"),
+            Self::WrongLength { expected, actual } => {
+                write!(f, "wrong length: expected {expected} bytes, got {actual}")
+            }
+            Self::ContextTooLong => f.write_str("context string too long"),
+            Self::SecretKeyOutOfRange => f.write_str("secret key coefficient out of range"),
+            Self::InconsistentSecretKey => f.write_str("inconsistent secret key"),
+            Self::KeyPairMismatch => f.write_str("public key does not match secret key"),
+            Self::InvalidExpandedKey => f.write_str("invalid expanded key"),
+            Self::BufferTooSmall { needed, actual } => {
+                write!(f, "buffer too small: needed {needed} bytes, got {actual}")
+            }
+            Self::MalformedEncoding => f.write_str("malformed encoding"),
+            Self::AlgorithmMismatch => f.write_str("algorithm mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
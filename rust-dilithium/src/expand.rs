@@ -1,105 +1,148 @@
 use crate::{
     coefficient,
     counter::Counter,
+    packing::Pack,
+    params::{Dimension, ParameterSet},
     polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial, NB_COEFFICIENTS},
     subarr_mut,
     subarray::Subarray,
-    vector::{Matrix, Vector},
-    ArrayChunks, TryCollectArray, ETA, GAMMA1, K, L, Q,
+    vector::{Matrix, TryCollectVector, Vector},
+    z_unpacker_18bits, z_unpacker_20bits, TryCollectArray, Q,
 };
 use core::mem::size_of;
-use itertools::iproduct;
+use zeroize::{Zeroize, Zeroizing};
 
 #[inline(never)]
-pub fn expand_a(mut ctr: impl Counter) -> Matrix<NTTPolynomial, L, K> {
+pub fn expand_a<P: ParameterSet>(mut ctr: impl Counter) -> Matrix<NTTPolynomial, P::L, P::K> {
+    // Each of the `K` rows is used to create a `Vector` of size `L`
+    let retval_it = (0..P::K::N).map(|i| {
+        let row_it = (0..P::L::N).map(|j| expand_a_polynomial(&mut ctr, i, j));
+        row_it.try_collect_vector().unwrap()
+    });
+
+    // Unwrapping is safe since both iterators yield exactly the right number of items
+    retval_it.try_collect_vector().unwrap()
+}
+
+/// Generates the matrix `A` as [`expand_a`] does, directly into `dst`
+pub fn expand_a_into<P: ParameterSet>(
+    mut ctr: impl Counter,
+    dst: &mut Matrix<NTTPolynomial, P::L, P::K>,
+) {
+    for (i, row) in dst.into_iter().enumerate() {
+        for (j, dst_coeff) in row.into_iter().enumerate() {
+            *dst_coeff = expand_a_polynomial(&mut ctr, i, j);
+        }
+    }
+}
+
+/// Generates the coefficient of `A` at row `i` and column `j` with rejection sampling, so that
+/// the matrix can also be generated one polynomial at a time
+pub fn expand_a_polynomial(ctr: &mut impl Counter, i: usize, j: usize) -> NTTPolynomial {
     const _23BITS_MASK: coefficient::Coefficient = (1 << 23) - 1;
     const _23BITS_MASK_SIZE: usize = 3;
 
     let mut block_buf = [0; size_of::<coefficient::Coefficient>()];
 
-    let retval_it = iproduct!(0..K as u16, 0..L as u16)
-        // For each of the `K * L` coefficients of our return value, we generate a polynomial with
-        // rejection sampling
-        .map(|(i, j)| {
-            ctr.reset(256 * i + j);
-
-            let polynomial_it = sample_polynomial(0, Q - 1, |_| {
-                *subarr_mut!(block_buf[.._23BITS_MASK_SIZE]) = ctr.squeeze();
-                coefficient::Coefficient::from_le_bytes(block_buf) & _23BITS_MASK
-            });
-
-            NTTPolynomial::from(polynomial_it.try_collect_array().unwrap())
-        })
-        // We divide the result into `K` chunks of size `L` and each chunk is used to create a
-        // `Vector` of size L
-        ._array_chunks()
-        .map(Vector::from);
-
-    // Therefore, unwrapping here should be safe
-    Matrix::from(retval_it.try_collect_array().unwrap())
+    ctr.reset(256 * i as u16 + j as u16);
+
+    let polynomial_it = sample_polynomial(0, Q - 1, |_| {
+        *subarr_mut!(block_buf[.._23BITS_MASK_SIZE]) = ctr.squeeze();
+        coefficient::Coefficient::from_le_bytes(block_buf) & _23BITS_MASK
+    });
+
+    NTTPolynomial::from(polynomial_it.try_collect_array().unwrap())
 }
 
 #[inline(never)]
-pub fn expand_s<const N: usize>(mut ctr: impl Counter, nonce: u16) -> Vector<PlainPolynomial, N> {
-    let mut block_buf_opt: Option<[u8; 1]> = None;
+pub fn expand_s<P: ParameterSet, N: Dimension>(
+    mut ctr: impl Counter,
+    nonce: u16,
+) -> Vector<PlainPolynomial, N> {
+    let retval_it =
+        (nonce..nonce + N::N as u16).map(|nonce| expand_s_polynomial::<P>(&mut ctr, nonce));
+
+    // Unwrapping is safe since the iterator yields exactly `N` polynomials
+    retval_it.try_collect_vector().unwrap()
+}
 
-    // For each of the `N` coefficients of our return value, we generate a polynomial with
-    // rejection sampling
-    let retval_it = (nonce..nonce + N as u16).map(|nonce| {
-        ctr.reset(nonce);
-        block_buf_opt = None;
-
-        let polynomial_it = sample_polynomial(0, 14, |_| match block_buf_opt {
-            None => {
-                let block_buf: [u8; 1] = ctr.squeeze();
-                block_buf_opt = Some(block_buf);
-                (block_buf[0] & 0xf) as coefficient::Coefficient
-            }
-            Some(block_buf) => {
-                block_buf_opt = None;
-                (block_buf[0] >> 4) as coefficient::Coefficient
-            }
-        })
-        .map(|coeff| ETA - (coeff % (2 * ETA + 1)));
-
-        PlainPolynomial::from(polynomial_it.try_collect_array().unwrap())
-    });
+/// Generates a secret vector as [`expand_s`] does, directly into `dst`
+pub fn expand_s_into<P: ParameterSet, N: Dimension>(
+    mut ctr: impl Counter,
+    nonce: u16,
+    dst: &mut Vector<PlainPolynomial, N>,
+) {
+    for (nonce, dst_coeff) in (nonce..).zip(dst) {
+        *dst_coeff = expand_s_polynomial::<P>(&mut ctr, nonce);
+    }
+}
 
-    // Therefore, unwrapping should be safe here
-    Vector::from(retval_it.try_collect_array().unwrap())
+/// Generates the polynomial of a secret vector for `nonce` with rejection sampling
+pub fn expand_s_polynomial<P: ParameterSet>(ctr: &mut impl Counter, nonce: u16) -> PlainPolynomial {
+    let mut block_buf_opt: Option<[u8; 1]> = None;
+    let sup = if P::ETA == 2 { 14 } else { 8 };
+
+    ctr.reset(nonce);
+
+    let polynomial_it = sample_polynomial(0, sup, |_| match block_buf_opt {
+        None => {
+            let block_buf: [u8; 1] = ctr.squeeze();
+            block_buf_opt = Some(block_buf);
+            (block_buf[0] & 0xf) as coefficient::Coefficient
+        }
+        Some(block_buf) => {
+            block_buf_opt = None;
+            (block_buf[0] >> 4) as coefficient::Coefficient
+        }
+    })
+    .map(|coeff| P::ETA - (coeff % (2 * P::ETA + 1)));
+
+    let retval = PlainPolynomial::from(polynomial_it.try_collect_array().unwrap());
+    block_buf_opt.zeroize();
+
+    retval
 }
 
 #[inline(never)]
-pub fn expand_y(mut ctr: impl Counter, nonce: u16) -> Vector<PlainPolynomial, L> {
-    let mut block_buf = [0; 3];
-
-    // For each of the `L` coefficients of our return value, we generate a polynomial with
-    // rejection sampling
-    let retval_it = (L as u16 * nonce..L as u16 * (nonce + 1)).map(|nonce| {
-        ctr.reset(nonce);
-
-        let polynomial_it = sample_polynomial(1 - GAMMA1, GAMMA1, |i| {
-            let k = 4 * (i % 2);
-
-            if k == 0 {
-                block_buf = ctr.squeeze();
-            } else {
-                block_buf[0] = block_buf[2];
-                *subarr_mut!(block_buf[1..3]) = ctr.squeeze();
-            }
-
-            let mut coeff = (block_buf[0] as coefficient::Coefficient) >> k;
-            coeff |= (block_buf[1] as coefficient::Coefficient) << (8 - k);
-            coeff |= (block_buf[2] as coefficient::Coefficient) << (16 - k);
-            coeff &= 0xfffff;
-            GAMMA1 - coeff
-        });
-
-        PlainPolynomial::from(polynomial_it.try_collect_array().unwrap())
-    });
+pub fn expand_y<P: ParameterSet>(
+    mut ctr: impl Counter,
+    nonce: u16,
+) -> Vector<PlainPolynomial, P::L> {
+    let l = P::L::N as u16;
+
+    let retval_it =
+        (l * nonce..l * (nonce + 1)).map(|nonce| expand_y_polynomial::<P>(&mut ctr, nonce));
+
+    // Unwrapping is safe since the iterator yields exactly `L` polynomials
+    retval_it.try_collect_vector().unwrap()
+}
+
+/// Generates the masking vector as [`expand_y`] does, directly into `dst`
+pub fn expand_y_into<P: ParameterSet>(
+    mut ctr: impl Counter,
+    nonce: u16,
+    dst: &mut Vector<PlainPolynomial, P::L>,
+) {
+    let l = P::L::N as u16;
+
+    for (nonce, dst_coeff) in (l * nonce..).zip(dst) {
+        *dst_coeff = expand_y_polynomial::<P>(&mut ctr, nonce);
+    }
+}
 
-    // Therefore, unwrapping should be safe here
-    Vector::from(retval_it.try_collect_array().unwrap())
+/// Generates the polynomial of the masking vector for `nonce`, whose coefficients are read from
+/// the stream the same way `z` is unpacked from a signature. The `L` polynomials of the masking
+/// vector of an attempt use consecutive nonces.
+pub fn expand_y_polynomial<P: ParameterSet>(ctr: &mut impl Counter, nonce: u16) -> PlainPolynomial {
+    ctr.reset(nonce);
+
+    if P::GAMMA1 == 1 << 17 {
+        let block_buf = Zeroizing::new(ctr.squeeze::<576>());
+        PlainPolynomial::unpack(&*block_buf, &z_unpacker_18bits)
+    } else {
+        let block_buf = Zeroizing::new(ctr.squeeze::<640>());
+        PlainPolynomial::unpack(&*block_buf, &z_unpacker_20bits)
+    }
 }
 
 fn sample_polynomial(
@@ -0,0 +1,324 @@
+//! Keys expanded once for signing or verifying many messages
+//!
+//! Signing with a packed secret key expands the matrix `A` from `rho` and moves the secret vectors
+//! to the NTT domain, and verifying against a packed public key expands `A`, hashes the public key
+//! and moves `t1 * 2^d` to the NTT domain. The expanded keys do this work when they are made, and
+//! yield the same signatures and verification results as the functions at the root of the crate.
+//!
+//! The matrix depends on the counter, so the expanded keys are bound to it. They can be stored as
+//! blobs of `SIZE` bytes, in which each coefficient of the NTT domain is reduced modulo `Q` and
+//! takes 3 bytes.
+
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{
+    coefficient::{self, Coefficient},
+    compute_ss_and_t,
+    counter::Counter,
+    expand, make_tr,
+    packing::Pack,
+    params::Dimension,
+    polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial, NB_COEFFICIENTS},
+    public_key_partition_sizes, secret_key_partition_sizes, sign_expanded_internal, t1_unpacker,
+    unpack_signature,
+    vector::{Matrix, Vector},
+    verify_expanded_internal, Error, Mu, ParameterSet, Partition, PartitionMut, PublicKey,
+    SecretKey, Signature, Q, RANDOMNESS_SIZE, SEED_SIZE,
+};
+
+const NTT_PACKED_SIZE: usize = 3 * NB_COEFFICIENTS;
+
+pub struct ExpandedSigningKey<P: ParameterSet, Ctr: Counter> {
+    key: [u8; SEED_SIZE / 2],
+    tr: [u8; SEED_SIZE],
+    a: Matrix<NTTPolynomial, P::L, P::K>,
+    s1: Vector<NTTPolynomial, P::L>,
+    s2: Vector<NTTPolynomial, P::K>,
+    t0: Vector<NTTPolynomial, P::K>,
+    phantom: PhantomData<Ctr>,
+}
+
+impl<P: ParameterSet, Ctr: Counter> ExpandedSigningKey<P, Ctr> {
+    pub const SIZE: usize =
+        SEED_SIZE / 2 + P::TR_SIZE + (P::K::N * P::L::N + P::L::N + 2 * P::K::N) * NTT_PACKED_SIZE;
+
+    pub fn new(sk: &SecretKey<P>) -> Self {
+        let [rho, key, tr, packed_s1, packed_s2, packed_t0] =
+            sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+        let (s1, s2, t0) = compute_ss_and_t::<P>(packed_s1, packed_s2, packed_t0);
+        let mut retval_tr = [0u8; SEED_SIZE];
+        retval_tr[..P::TR_SIZE].copy_from_slice(tr);
+
+        Self {
+            // Unwrapping is safe here because the slices are of the right size
+            key: key.try_into().unwrap(),
+            tr: retval_tr,
+            a: expand::expand_a::<P>(Ctr::new(rho.try_into().unwrap())),
+            s1,
+            s2,
+            t0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Fails if `bytes` is not `SIZE` bytes long, or if a coefficient is not reduced modulo `Q`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        check_length(bytes, Self::SIZE)?;
+
+        let [key, tr, packed_a, packed_s1, packed_s2, packed_t0] =
+            bytes.partition(&Self::partition_sizes());
+        let mut retval_tr = [0u8; SEED_SIZE];
+        retval_tr[..P::TR_SIZE].copy_from_slice(tr);
+
+        Ok(Self {
+            key: key.try_into().unwrap(),
+            tr: retval_tr,
+            a: unpack_ntt(packed_a)?,
+            s1: unpack_ntt(packed_s1)?,
+            s2: unpack_ntt(packed_s2)?,
+            t0: unpack_ntt(packed_t0)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Writes the blob of the key to `output`, which must be `SIZE` bytes long
+    pub fn write_bytes(&self, output: &mut [u8]) -> Result<(), Error> {
+        check_length(output, Self::SIZE)?;
+
+        let [key, tr, packed_a, packed_s1, packed_s2, packed_t0] =
+            output.partition_mut(&Self::partition_sizes());
+
+        key.copy_from_slice(&self.key);
+        tr.copy_from_slice(&self.tr[..P::TR_SIZE]);
+        self.a.pack_inplace(&ntt_packer, packed_a);
+        self.s1.pack_inplace(&ntt_packer, packed_s1);
+        self.s2.pack_inplace(&ntt_packer, packed_s2);
+        self.t0.pack_inplace(&ntt_packer, packed_t0);
+
+        Ok(())
+    }
+
+    /// Signs `msg` in the deterministic variant, see [`crate::sign`]
+    pub fn sign(&self, msg: &[u8]) -> Result<Signature<P>, Error> {
+        self.sign_mu(&self.hash_message(msg))
+    }
+
+    /// Signs `msg` in the hedged variant, where `rnd` must be fresh randomness, see
+    /// [`crate::sign_hedged`]
+    pub fn sign_hedged(
+        &self,
+        msg: &[u8],
+        rnd: &[u8; RANDOMNESS_SIZE],
+    ) -> Result<Signature<P>, Error> {
+        self.sign_mu_hedged(&self.hash_message(msg), rnd)
+    }
+
+    /// Signs a message given by its representative `mu`, see [`crate::sign_mu`]
+    pub fn sign_mu(&self, mu: &Mu) -> Result<Signature<P>, Error> {
+        Ok(self.sign_internal(mu, &[]))
+    }
+
+    /// Signs a message given by its representative `mu` in the hedged variant, see
+    /// [`crate::sign_mu_hedged`]
+    pub fn sign_mu_hedged(
+        &self,
+        mu: &Mu,
+        rnd: &[u8; RANDOMNESS_SIZE],
+    ) -> Result<Signature<P>, Error> {
+        Ok(self.sign_internal(mu, rnd))
+    }
+
+    pub(crate) fn tr(&self) -> &[u8] {
+        &self.tr[..P::TR_SIZE]
+    }
+
+    pub(crate) fn sign_internal(&self, mu: &Mu, rnd: &[u8]) -> Signature<P> {
+        sign_expanded_internal::<P, Ctr>(mu, rnd, &self.key, &self.a, &self.s1, &self.s2, &self.t0)
+    }
+
+    fn hash_message(&self, msg: &[u8]) -> Mu {
+        crate::hash_message(self.tr(), msg)
+    }
+
+    fn partition_sizes() -> [usize; 6] {
+        [
+            SEED_SIZE / 2,
+            P::TR_SIZE,
+            P::K::N * P::L::N * NTT_PACKED_SIZE,
+            P::L::N * NTT_PACKED_SIZE,
+            P::K::N * NTT_PACKED_SIZE,
+            P::K::N * NTT_PACKED_SIZE,
+        ]
+    }
+}
+
+pub struct ExpandedVerifyingKey<P: ParameterSet, Ctr: Counter> {
+    tr: [u8; SEED_SIZE],
+    a: Matrix<NTTPolynomial, P::L, P::K>,
+    shifted_t1: Vector<NTTPolynomial, P::K>,
+    phantom: PhantomData<Ctr>,
+}
+
+impl<P: ParameterSet, Ctr: Counter> ExpandedVerifyingKey<P, Ctr> {
+    pub const SIZE: usize = P::TR_SIZE + (P::K::N * P::L::N + P::K::N) * NTT_PACKED_SIZE;
+
+    pub fn new(pk: &PublicKey<P>) -> Self {
+        let [rho, packed_t1] = pk.as_bytes().partition(&public_key_partition_sizes::<P>());
+        let t1: Vector<PlainPolynomial, P::K> = Pack::unpack(packed_t1, &t1_unpacker);
+
+        Self {
+            tr: make_tr::<P>(pk),
+            // Unwrapping is safe here because the slice is of the right size
+            a: expand::expand_a::<P>(Ctr::new(rho.try_into().unwrap())),
+            shifted_t1: t1.shift_d().into_ntt(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Fails if `bytes` is not `SIZE` bytes long, or if a coefficient is not reduced modulo `Q`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        check_length(bytes, Self::SIZE)?;
+
+        let [tr, packed_a, packed_t1] = bytes.partition(&Self::partition_sizes());
+        let mut retval_tr = [0u8; SEED_SIZE];
+        retval_tr[..P::TR_SIZE].copy_from_slice(tr);
+
+        Ok(Self {
+            tr: retval_tr,
+            a: unpack_ntt(packed_a)?,
+            shifted_t1: unpack_ntt(packed_t1)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Writes the blob of the key to `output`, which must be `SIZE` bytes long
+    pub fn write_bytes(&self, output: &mut [u8]) -> Result<(), Error> {
+        check_length(output, Self::SIZE)?;
+
+        let [tr, packed_a, packed_t1] = output.partition_mut(&Self::partition_sizes());
+
+        tr.copy_from_slice(&self.tr[..P::TR_SIZE]);
+        self.a.pack_inplace(&ntt_packer, packed_a);
+        self.shifted_t1.pack_inplace(&ntt_packer, packed_t1);
+
+        Ok(())
+    }
+
+    /// Verifies `signature` against `msg`, see [`crate::verify`]
+    pub fn verify(&self, msg: &[u8], signature: &Signature<P>) -> Result<(), Error> {
+        self.verify_mu(&crate::hash_message(self.tr(), msg), signature)
+    }
+
+    /// Verifies `signature` against a message given by its representative `mu`, see
+    /// [`crate::verify_mu`]
+    pub fn verify_mu(&self, mu: &Mu, signature: &Signature<P>) -> Result<(), Error> {
+        let unpacked_signature = unpack_signature::<P>(signature.as_bytes())?;
+
+        verify_expanded_internal::<P>(mu, unpacked_signature, &self.a, self.shifted_t1.clone())
+    }
+
+    pub(crate) fn tr(&self) -> &[u8] {
+        &self.tr[..P::TR_SIZE]
+    }
+
+    fn partition_sizes() -> [usize; 3] {
+        [
+            P::TR_SIZE,
+            P::K::N * P::L::N * NTT_PACKED_SIZE,
+            P::K::N * NTT_PACKED_SIZE,
+        ]
+    }
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), Error> {
+    if bytes.len() == expected {
+        Ok(())
+    } else {
+        Err(Error::WrongLength {
+            expected,
+            actual: bytes.len(),
+        })
+    }
+}
+
+/// Unpacks polynomials of the NTT domain, rejecting the coefficients that are not reduced
+fn unpack_ntt<T: Pack<Input = Coefficient, Output = u8>>(packed: &[u8]) -> Result<T, Error> {
+    let is_reduced = packed
+        .chunks_exact(3)
+        .all(|chunk| ntt_unpacker(chunk.try_into().unwrap())[0] < Q);
+
+    if is_reduced {
+        Ok(T::unpack(packed, &ntt_unpacker))
+    } else {
+        Err(Error::InvalidExpandedKey)
+    }
+}
+
+fn ntt_packer(chunk: &[Coefficient; 1]) -> [u8; 3] {
+    let coeff = coefficient::caddq(coefficient::reduce_32(chunk[0]));
+    let [b0, b1, b2, _] = coeff.to_le_bytes();
+
+    [b0, b1, b2]
+}
+
+fn ntt_unpacker(chunk: &[u8; 3]) -> [Coefficient; 1] {
+    [Coefficient::from_le_bytes([
+        chunk[0], chunk[1], chunk[2], 0,
+    ])]
+}
+
+// The traits are implemented by hand since deriving them would require the parameter set and the
+// counter themselves to implement them
+
+impl<P: ParameterSet, Ctr: Counter> Clone for ExpandedSigningKey<P, Ctr> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key,
+            tr: self.tr,
+            a: self.a.clone(),
+            s1: self.s1.clone(),
+            s2: self.s2.clone(),
+            t0: self.t0.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Debug for ExpandedSigningKey<P, Ctr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpandedSigningKey").finish_non_exhaustive()
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Drop for ExpandedSigningKey<P, Ctr> {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> ZeroizeOnDrop for ExpandedSigningKey<P, Ctr> {}
+
+impl<P: ParameterSet, Ctr: Counter> Clone for ExpandedVerifyingKey<P, Ctr> {
+    fn clone(&self) -> Self {
+        Self {
+            tr: self.tr,
+            a: self.a.clone(),
+            shifted_t1: self.shifted_t1.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Debug for ExpandedVerifyingKey<P, Ctr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpandedVerifyingKey")
+            .field("tr", &self.tr())
+            .finish_non_exhaustive()
+    }
+}
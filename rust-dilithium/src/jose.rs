@@ -0,0 +1,483 @@
+//! JWS compact serializations and JWK encodings of ML-DSA and round-3 Dilithium public keys
+//!
+//! The ML-DSA algorithms are named as in the IETF JOSE draft for ML-DSA, with an empty context
+//! string, and the keys use the algorithm key pair (`AKP`) key type. As for [`crate::cose`],
+//! round-3 Dilithium has no registered names, so its serializations and keys use names that the
+//! caller picks through [`DilithiumJoseAlgorithm`], and carry the signatures of [`crate::sign`].
+//!
+//! The serializations are written into slices, so no allocator is needed, and their sizes are
+//! known from the sizes of the signature and of the public key, see [`jws_size`] and
+//! [`jwk_size`]. Verification requires the `alg` header parameter, rejects the `crit` one as it
+//! understands no extension, and rejects protected headers larger than 256 bytes. Unencoded and
+//! detached payloads are not supported.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+
+use crate::{
+    counter::Counter,
+    envelope::{Dilithium, MlDsa, Scheme},
+    params::ByteArray,
+    Error, MlDsa44, MlDsa65, MlDsa87, ParameterSet, PublicKey, SecretKey, Signature,
+    RANDOMNESS_SIZE,
+};
+
+pub const ML_DSA_44: &str = "ML-DSA-44";
+pub const ML_DSA_65: &str = "ML-DSA-65";
+pub const ML_DSA_87: &str = "ML-DSA-87";
+
+/// Key type of algorithm key pairs, which carry the algorithm along with the key
+pub const KEY_TYPE_AKP: &str = "AKP";
+
+const HEADER_PREFIX: &str = r#"{"alg":""#;
+const HEADER_SUFFIX: &str = r#""}"#;
+
+const JWK_PREFIX: &str = r#"{"kty":"AKP","alg":""#;
+const JWK_INFIX: &str = r#"","pub":""#;
+const JWK_SUFFIX: &str = r#""}"#;
+
+/// Size above which the protected header is rejected
+const HEADER_SIZE_MAX: usize = 256;
+/// Depth of nesting above which the JSON values are rejected
+const DEPTH_MAX: usize = 8;
+
+/// Signature algorithm of JWS serializations and of JWK keys, with its JOSE algorithm name
+///
+/// It is implemented by the ML-DSA parameter sets, with the names of the IETF draft, and by the
+/// implementations of [`DilithiumJoseAlgorithm`].
+pub trait JoseAlgorithm {
+    type ParameterSet: ParameterSet;
+    /// Scheme that signs the signing input, which only this crate provides
+    type Scheme: Scheme<ParameterSet = Self::ParameterSet>;
+
+    const ALGORITHM: &'static str;
+}
+
+/// Round-3 Dilithium parameter set and counter, under a JOSE algorithm name picked by the caller
+///
+/// No name is registered for round-3 Dilithium, so it should be collision-resistant, such as a URI
+/// under the control of the caller. The signing input is signed as a message by [`crate::sign`],
+/// or by [`crate::sign_hedged`] in the hedged variant, and verified by [`crate::verify`]. The name
+/// must not need escaping in a JSON string.
+pub trait DilithiumJoseAlgorithm {
+    type ParameterSet: ParameterSet;
+    type Counter: Counter;
+
+    const ALGORITHM: &'static str;
+}
+
+impl JoseAlgorithm for MlDsa44 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: &'static str = ML_DSA_44;
+}
+
+impl JoseAlgorithm for MlDsa65 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: &'static str = ML_DSA_65;
+}
+
+impl JoseAlgorithm for MlDsa87 {
+    type ParameterSet = Self;
+    type Scheme = MlDsa<Self>;
+
+    const ALGORITHM: &'static str = ML_DSA_87;
+}
+
+impl<A: DilithiumJoseAlgorithm> JoseAlgorithm for A {
+    type ParameterSet = A::ParameterSet;
+    type Scheme = Dilithium<A::ParameterSet, A::Counter>;
+
+    const ALGORITHM: &'static str = A::ALGORITHM;
+}
+
+/// Returns the size of the JWS of a payload of `payload_size` bytes
+pub const fn jws_size<A: JoseAlgorithm>(payload_size: usize) -> usize {
+    base64_size(header_size::<A>())
+        + 1
+        + base64_size(payload_size)
+        + 1
+        + base64_size(<A::ParameterSet as ParameterSet>::SIGNATURE_SIZE)
+}
+
+/// Returns the size of the JWK of a public key
+pub const fn jwk_size<A: JoseAlgorithm>() -> usize {
+    JWK_PREFIX.len()
+        + A::ALGORITHM.len()
+        + JWK_INFIX.len()
+        + base64_size(<A::ParameterSet as ParameterSet>::PUBLIC_KEY_SIZE)
+        + JWK_SUFFIX.len()
+}
+
+/// Signs `payload` in the hedged variant, where `rnd` must be fresh randomness, and writes its JWS
+/// in the compact serialization to `out`
+///
+/// Fails with [`Error::BufferTooSmall`] if `out` is smaller than [`jws_size`].
+pub fn sign_jws<'o, A: JoseAlgorithm>(
+    payload: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+    out: &'o mut [u8],
+) -> Result<&'o str, Error> {
+    sign_jws_with::<A>(payload, sk, Some(rnd), out)
+}
+
+/// Signs `payload` as [`sign_jws`] does in the deterministic variant
+pub fn sign_jws_deterministic<'o, A: JoseAlgorithm>(
+    payload: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    out: &'o mut [u8],
+) -> Result<&'o str, Error> {
+    sign_jws_with::<A>(payload, sk, None, out)
+}
+
+/// Verifies the JWS `jws` in the compact serialization, and decodes its payload into `payload_out`
+///
+/// Fails with [`Error::MalformedEncoding`] if `jws` is not a well-formed JWS, with
+/// [`Error::AlgorithmMismatch`] if its header does not name the algorithm of `A`, and with
+/// [`Error::BufferTooSmall`] if `payload_out` cannot hold the payload.
+pub fn verify_jws<'o, A: JoseAlgorithm>(
+    jws: &str,
+    pk: &PublicKey<A::ParameterSet>,
+    payload_out: &'o mut [u8],
+) -> Result<&'o [u8], Error> {
+    let mut parts = jws.split('.');
+    let (Some(header), Some(payload), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::MalformedEncoding);
+    };
+    let signing_input = &jws.as_bytes()[..header.len() + 1 + payload.len()];
+
+    let mut header_bytes = [0; HEADER_SIZE_MAX];
+    check_header::<A>(decode_base64(header.as_bytes(), &mut header_bytes)?)?;
+
+    let mut signature_bytes = <A::ParameterSet as ParameterSet>::SignatureBytes::zeroed();
+    let signature = Signature::<A::ParameterSet>::from_bytes(decode_base64(
+        signature.as_bytes(),
+        signature_bytes.as_mut(),
+    )?)?;
+
+    let needed = payload.len() * 3 / 4;
+    let actual = payload_out.len();
+    let payload_out = payload_out
+        .get_mut(..needed)
+        .ok_or(Error::BufferTooSmall { needed, actual })?;
+    let payload = decode_base64(payload.as_bytes(), payload_out)?;
+
+    A::Scheme::verify_parts(&[signing_input], &signature, pk)?;
+
+    Ok(payload)
+}
+
+/// Writes the JWK of `pk` to `out`
+///
+/// Fails with [`Error::BufferTooSmall`] if `out` is smaller than [`jwk_size`].
+pub fn encode_jwk<'o, A: JoseAlgorithm>(
+    pk: &PublicKey<A::ParameterSet>,
+    out: &'o mut [u8],
+) -> Result<&'o str, Error> {
+    let mut writer = Writer::new(out, jwk_size::<A>())?;
+
+    writer.raw(JWK_PREFIX.as_bytes());
+    writer.raw(A::ALGORITHM.as_bytes());
+    writer.raw(JWK_INFIX.as_bytes());
+    writer.base64(pk.as_bytes());
+    writer.raw(JWK_SUFFIX.as_bytes());
+
+    writer.finish()
+}
+
+/// Decodes the public key of the JWK `jwk`, ignoring the parameters other than its key type, its
+/// algorithm and its public key
+///
+/// Fails with [`Error::AlgorithmMismatch`] if the key is not an `AKP` key of the algorithm of `A`.
+pub fn decode_jwk<A: JoseAlgorithm>(jwk: &str) -> Result<PublicKey<A::ParameterSet>, Error> {
+    let (mut key_type, mut algorithm, mut pk) = (None, None, None);
+
+    for_each_member(jwk.as_bytes(), |name, value| {
+        let field = match name {
+            b"kty" => &mut key_type,
+            b"alg" => &mut algorithm,
+            b"pub" => &mut pk,
+            _ => return Ok(()),
+        };
+
+        match field.replace(string(value)?) {
+            Some(_) => Err(Error::MalformedEncoding),
+            None => Ok(()),
+        }
+    })?;
+
+    if key_type != Some(KEY_TYPE_AKP.as_bytes()) || algorithm != Some(A::ALGORITHM.as_bytes()) {
+        return Err(Error::AlgorithmMismatch);
+    }
+
+    let pk = pk.ok_or(Error::MalformedEncoding)?;
+    let mut pk_bytes = <A::ParameterSet as ParameterSet>::PublicKeyBytes::zeroed();
+
+    PublicKey::from_bytes(decode_base64(pk, pk_bytes.as_mut())?)
+}
+
+/// Signs `payload` as [`sign_jws`] does, in the deterministic variant when `rnd` is `None`
+fn sign_jws_with<'o, A: JoseAlgorithm>(
+    payload: &[u8],
+    sk: &SecretKey<A::ParameterSet>,
+    rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+    out: &'o mut [u8],
+) -> Result<&'o str, Error> {
+    let mut writer = Writer::new(out, jws_size::<A>(payload.len()))?;
+    let mut header = [0; HEADER_SIZE_MAX];
+    let header = encode_header::<A>(&mut header);
+
+    writer.base64(header);
+    writer.raw(b".");
+    writer.base64(payload);
+
+    // The signing input is the beginning of the serialization
+    let signature = A::Scheme::sign_parts(&[writer.written()], sk, rnd)?;
+
+    writer.raw(b".");
+    writer.base64(signature.as_bytes());
+
+    writer.finish()
+}
+
+const fn header_size<A: JoseAlgorithm>() -> usize {
+    HEADER_PREFIX.len() + A::ALGORITHM.len() + HEADER_SUFFIX.len()
+}
+
+/// Returns the size of the unpadded Base64 encoding of `size` bytes
+const fn base64_size(size: usize) -> usize {
+    (size * 4).div_ceil(3)
+}
+
+/// Encodes the protected header of `A` into `buffer`, which only holds the algorithm
+fn encode_header<A: JoseAlgorithm>(buffer: &mut [u8]) -> &[u8] {
+    // The name of the caller would otherwise make the header rejected by `verify_jws`
+    const { assert!(header_size::<A>() <= HEADER_SIZE_MAX) };
+
+    let mut writer = Writer {
+        out: buffer,
+        len: 0,
+    };
+
+    writer.raw(HEADER_PREFIX.as_bytes());
+    writer.raw(A::ALGORITHM.as_bytes());
+    writer.raw(HEADER_SUFFIX.as_bytes());
+
+    writer.into_bytes()
+}
+
+/// Checks that `header` names the algorithm of `A`, and no critical extension
+fn check_header<A: JoseAlgorithm>(header: &[u8]) -> Result<(), Error> {
+    let mut algorithm = None;
+
+    for_each_member(header, |name, value| match name {
+        b"alg" if algorithm.is_none() => {
+            algorithm = Some(string(value)?);
+            Ok(())
+        }
+        b"alg" | b"crit" => Err(Error::MalformedEncoding),
+        _ => Ok(()),
+    })?;
+
+    match algorithm {
+        Some(algorithm) if algorithm == A::ALGORITHM.as_bytes() => Ok(()),
+        _ => Err(Error::AlgorithmMismatch),
+    }
+}
+
+/// Decodes the unpadded Base64url `encoded` into the beginning of `out`
+fn decode_base64<'o>(encoded: &[u8], out: &'o mut [u8]) -> Result<&'o [u8], Error> {
+    Base64UrlUnpadded::decode(encoded, out).map_err(|_| Error::MalformedEncoding)
+}
+
+/// Returns the contents of the JSON string `value`, which must not need unescaping
+fn string(value: &[u8]) -> Result<&[u8], Error> {
+    match value {
+        [b'"', contents @ .., b'"'] if !contents.contains(&b'\\') => Ok(contents),
+        _ => Err(Error::MalformedEncoding),
+    }
+}
+
+/// Calls `f` with the name and the raw value of each member of the JSON object `json`
+///
+/// The names must not need unescaping, so that no name can pass for another.
+fn for_each_member<'a>(
+    json: &'a [u8],
+    mut f: impl FnMut(&'a [u8], &'a [u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut scanner = Scanner { input: json };
+    scanner.punctuation(b'{')?;
+
+    if scanner.peek() == Some(b'}') {
+        scanner.punctuation(b'}')?;
+    } else {
+        loop {
+            let name = string(scanner.value(0)?)?;
+            scanner.punctuation(b':')?;
+            f(name, scanner.value(0)?)?;
+
+            match scanner.peek() {
+                Some(b',') => scanner.punctuation(b',')?,
+                _ => break scanner.punctuation(b'}')?,
+            }
+        }
+    }
+
+    match scanner.peek() {
+        None => Ok(()),
+        Some(_) => Err(Error::MalformedEncoding),
+    }
+}
+
+/// Writer into a buffer whose size was checked beforehand
+struct Writer<'o> {
+    out: &'o mut [u8],
+    len: usize,
+}
+
+impl<'o> Writer<'o> {
+    fn new(out: &'o mut [u8], needed: usize) -> Result<Self, Error> {
+        match out.len() {
+            actual if actual < needed => Err(Error::BufferTooSmall { needed, actual }),
+            _ => Ok(Self { out, len: 0 }),
+        }
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        self.out[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn base64(&mut self, bytes: &[u8]) {
+        let size = base64_size(bytes.len());
+
+        // The size of the buffer was checked beforehand, so encoding cannot fail
+        if Base64UrlUnpadded::encode(bytes, &mut self.out[self.len..self.len + size]).is_ok() {
+            self.len += size;
+        }
+    }
+
+    fn written(&self) -> &[u8] {
+        &self.out[..self.len]
+    }
+
+    fn into_bytes(self) -> &'o [u8] {
+        &self.out[..self.len]
+    }
+
+    fn finish(self) -> Result<&'o str, Error> {
+        core::str::from_utf8(self.into_bytes()).map_err(|_| Error::MalformedEncoding)
+    }
+}
+
+/// Scanner of JSON values, which fails with [`Error::MalformedEncoding`]
+struct Scanner<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Scanner<'a> {
+    /// Skips whitespace and returns the next byte
+    fn peek(&mut self) -> Option<u8> {
+        let start = self
+            .input
+            .iter()
+            .position(|byte| !matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+            .unwrap_or(self.input.len());
+        self.input = &self.input[start..];
+
+        self.input.first().copied()
+    }
+
+    fn punctuation(&mut self, expected: u8) -> Result<(), Error> {
+        match self.peek() {
+            Some(byte) if byte == expected => {
+                self.input = &self.input[1..];
+                Ok(())
+            }
+            _ => Err(Error::MalformedEncoding),
+        }
+    }
+
+    /// Returns the raw bytes of the next value, nested at `depth`
+    fn value(&mut self, depth: usize) -> Result<&'a [u8], Error> {
+        if depth > DEPTH_MAX {
+            return Err(Error::MalformedEncoding);
+        }
+
+        let first = self.peek().ok_or(Error::MalformedEncoding)?;
+        let start = self.input;
+
+        match first {
+            b'"' => self.string()?,
+            b'{' | b'[' => self.container(depth)?,
+            b'-' | b'0'..=b'9' | b't' | b'f' | b'n' => {
+                // Numbers and literals run until the next delimiter
+                let len = self
+                    .input
+                    .iter()
+                    .position(|byte| {
+                        matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                    })
+                    .unwrap_or(self.input.len());
+                self.input = &self.input[len..];
+            }
+            _ => return Err(Error::MalformedEncoding),
+        }
+
+        Ok(&start[..start.len() - self.input.len()])
+    }
+
+    fn string(&mut self) -> Result<(), Error> {
+        let mut escaped = false;
+
+        for (i, &byte) in self.input.iter().enumerate().skip(1) {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    self.input = &self.input[i + 1..];
+                    return Ok(());
+                }
+                0..=0x1f => return Err(Error::MalformedEncoding),
+                _ => {}
+            }
+        }
+
+        Err(Error::MalformedEncoding)
+    }
+
+    fn container(&mut self, depth: usize) -> Result<(), Error> {
+        let (open, close) = match self.input[0] {
+            b'{' => (b'{', b'}'),
+            _ => (b'[', b']'),
+        };
+
+        self.punctuation(open)?;
+
+        if self.peek() == Some(close) {
+            return self.punctuation(close);
+        }
+
+        loop {
+            if open == b'{' {
+                if !self.value(depth + 1)?.starts_with(b"\"") {
+                    return Err(Error::MalformedEncoding);
+                }
+
+                self.punctuation(b':')?;
+            }
+
+            self.value(depth + 1)?;
+
+            match self.peek() {
+                Some(b',') => self.punctuation(b',')?,
+                _ => return self.punctuation(close),
+            }
+        }
+    }
+}
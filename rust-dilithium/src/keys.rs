@@ -0,0 +1,293 @@
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{params::ByteArray, unpack_signature, Error, ParameterSet, Seed};
+
+/// Packed public key
+pub struct PublicKey<P: ParameterSet>(P::PublicKeyBytes);
+
+/// Packed secret key, which is wiped when dropped and never printed
+pub struct SecretKey<P: ParameterSet>(P::SecretKeyBytes);
+
+/// Secret key stored as the seed it is derived from, which is expanded again by every signing
+/// call. It is wiped when dropped and never printed.
+pub struct CompactSecretKey<P: ParameterSet> {
+    seed: Seed,
+    phantom: PhantomData<P>,
+}
+
+/// Packed signature
+pub struct Signature<P: ParameterSet>(P::SignatureBytes);
+
+/// Public key along with the matching secret key
+pub struct KeyPair<P: ParameterSet> {
+    public_key: PublicKey<P>,
+    secret_key: SecretKey<P>,
+}
+
+impl<P: ParameterSet> PublicKey<P> {
+    /// Fails if `bytes` is not `PUBLIC_KEY_SIZE` bytes long
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        P::PublicKeyBytes::try_from_slice(bytes).map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    pub(crate) fn zeroed() -> Self {
+        Self(P::PublicKeyBytes::zeroed())
+    }
+
+    pub(crate) fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.0.as_mut()
+    }
+}
+
+impl<P: ParameterSet> SecretKey<P> {
+    /// Fails if `bytes` is not `SECRET_KEY_SIZE` bytes long
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        P::SecretKeyBytes::try_from_slice(bytes).map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    pub(crate) fn zeroed() -> Self {
+        Self(P::SecretKeyBytes::zeroed())
+    }
+
+    pub(crate) fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.0.as_mut()
+    }
+}
+
+impl<P: ParameterSet> CompactSecretKey<P> {
+    /// Wraps the seed that was passed to `make_keys`
+    pub fn new(seed: &Seed) -> Self {
+        Self {
+            seed: *seed,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Fails if `bytes` is not `SEED_SIZE / 2` bytes long
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Seed::try_from_slice(bytes).map(|seed| Self::new(&seed))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.seed
+    }
+
+    pub(crate) fn seed(&self) -> &Seed {
+        &self.seed
+    }
+}
+
+impl<P: ParameterSet> Signature<P> {
+    /// Fails if `bytes` is not `SIGNATURE_SIZE` bytes long, or if it is not the unique encoding of
+    /// a challenge seed, a response vector within the norm bound and a hint
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let retval = P::SignatureBytes::try_from_slice(bytes).map(Self)?;
+        unpack_signature::<P>(retval.as_bytes())?;
+
+        Ok(retval)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+
+    pub(crate) fn zeroed() -> Self {
+        Self(P::SignatureBytes::zeroed())
+    }
+
+    pub(crate) fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.0.as_mut()
+    }
+}
+
+impl<P: ParameterSet> KeyPair<P> {
+    pub fn new(public_key: PublicKey<P>, secret_key: SecretKey<P>) -> Self {
+        Self {
+            public_key,
+            secret_key,
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey<P> {
+        &self.public_key
+    }
+
+    pub fn secret_key(&self) -> &SecretKey<P> {
+        &self.secret_key
+    }
+
+    pub fn into_parts(self) -> (PublicKey<P>, SecretKey<P>) {
+        (self.public_key, self.secret_key)
+    }
+}
+
+impl<P: ParameterSet> From<(PublicKey<P>, SecretKey<P>)> for KeyPair<P> {
+    fn from((public_key, secret_key): (PublicKey<P>, SecretKey<P>)) -> Self {
+        Self::new(public_key, secret_key)
+    }
+}
+
+// The traits are implemented by hand since deriving them would require the parameter set itself
+// to implement them
+
+impl<P: ParameterSet> Clone for PublicKey<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: ParameterSet> Copy for PublicKey<P> {}
+
+impl<P: ParameterSet> PartialEq for PublicKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<P: ParameterSet> Eq for PublicKey<P> {}
+
+impl<P: ParameterSet> Debug for PublicKey<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PublicKey").field(&self.0).finish()
+    }
+}
+
+impl<P: ParameterSet> AsRef<[u8]> for PublicKey<P> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for PublicKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<P: ParameterSet> Clone for SecretKey<P> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+impl<P: ParameterSet> Debug for SecretKey<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKey").finish_non_exhaustive()
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for SecretKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<P: ParameterSet> Zeroize for SecretKey<P> {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+impl<P: ParameterSet> Drop for SecretKey<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<P: ParameterSet> ZeroizeOnDrop for SecretKey<P> {}
+
+impl<P: ParameterSet> Clone for CompactSecretKey<P> {
+    fn clone(&self) -> Self {
+        Self::new(&self.seed)
+    }
+}
+
+impl<P: ParameterSet> Debug for CompactSecretKey<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactSecretKey").finish_non_exhaustive()
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for CompactSecretKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<P: ParameterSet> Drop for CompactSecretKey<P> {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+impl<P: ParameterSet> ZeroizeOnDrop for CompactSecretKey<P> {}
+
+impl<P: ParameterSet> Clone for Signature<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: ParameterSet> Copy for Signature<P> {}
+
+impl<P: ParameterSet> PartialEq for Signature<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<P: ParameterSet> Eq for Signature<P> {}
+
+impl<P: ParameterSet> Debug for Signature<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Signature").field(&self.0).finish()
+    }
+}
+
+impl<P: ParameterSet> AsRef<[u8]> for Signature<P> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for Signature<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<P: ParameterSet> Clone for KeyPair<P> {
+    fn clone(&self) -> Self {
+        Self::new(self.public_key, self.secret_key.clone())
+    }
+}
+
+impl<P: ParameterSet> Debug for KeyPair<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public_key", &self.public_key)
+            .field("secret_key", &self.secret_key)
+            .finish()
+    }
+}
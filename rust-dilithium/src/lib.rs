@@ -4,129 +4,256 @@ use crate::packing::Pack;
 use coefficient::Coefficient;
 use core::{
     array::from_fn,
-    iter::{once, zip, Iterator},
-    mem::{size_of, MaybeUninit},
+    hint::black_box,
+    iter::{zip, Iterator},
+    mem::{size_of, take, MaybeUninit},
 };
 use counter::Counter;
-use polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial, NB_COEFFICIENTS};
+use expanded::ExpandedVerifyingKey;
+use params::Dimension;
+use polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial};
+use rand_core::CryptoRngCore;
 use sha3::{
     digest::{ExtendableOutput, ExtendableOutputReset, Update, XofReader},
     Shake256,
 };
-use subarray::Subarray;
 use vector::{Matrix, Vector};
+use zeroize::Zeroizing;
 
 mod array_math;
+#[cfg(feature = "der")]
+pub mod asn1;
 mod coefficient;
+#[cfg(feature = "cose")]
+pub mod cose;
 pub mod counter;
+#[cfg(any(feature = "cose", feature = "jose"))]
+mod envelope;
+mod error;
 mod expand;
+pub mod expanded;
+#[cfg(feature = "jose")]
+pub mod jose;
+mod keys;
+pub mod low_memory;
+pub mod mldsa;
 mod packing;
+pub mod params;
 mod polynomial;
+pub mod signing;
+pub mod stream;
 mod vector;
+pub mod workspace;
+#[cfg(feature = "x509")]
+pub mod x509;
 #[macro_use]
 mod subarray;
 
 #[cfg(test)]
 mod tests;
 
+pub use error::Error;
+pub use keys::{CompactSecretKey, KeyPair, PublicKey, SecretKey, Signature};
+pub use params::{Dilithium2, Dilithium3, Dilithium5, MlDsa44, MlDsa65, MlDsa87, ParameterSet};
+
 pub type Polynomial = [coefficient::Coefficient; POLYNOMIAL_DEGREE];
-pub type PublicKey = [u8; PUBLIC_KEY_SIZE];
-pub type SecretKey = [u8; SECRET_KEY_SIZE];
-pub type Signature = [u8; SIGNATURE_SIZE];
 pub type Seed = [u8; SEED_SIZE / 2];
+pub type Mu = [u8; SEED_SIZE];
 
-pub const K: usize = 8;
-pub const L: usize = 7;
 pub const SEED_SIZE: usize = 64;
 pub const HALF_SEED_SIZE: usize = 32;
+pub const RANDOMNESS_SIZE: usize = 32;
 const Q: coefficient::Coefficient = 8380417;
 const Q_MOD_2POW32_INVERSE: coefficient::Coefficient = 58728449;
 
-pub const PUBLIC_KEY_SIZE: usize = SEED_SIZE / 2 + K as usize * T1_PACKED_SIZE;
-pub const SECRET_KEY_SIZE: usize = 3 * SEED_SIZE / 2
-    + L as usize * ETA_PACKED_SIZE
-    + K as usize * ETA_PACKED_SIZE
-    + K * T0_PACKED_SIZE;
-const OMEGA: usize = 75;
-const BETA: Coefficient = 120;
-const ETA_PACKED_SIZE: usize = 96;
 const T0_PACKED_SIZE: usize = 416;
 const T1_PACKED_SIZE: usize = 320;
-const POLYZ_PACKED_SIZE: usize = 640;
-const POLYVECH_PACKED_SIZE: usize = OMEGA + K;
-const ETA: coefficient::Coefficient = 2;
+const POLYW1_PACKED_SIZE_MAX: usize = 192;
+const CHALLENGE_SEED_SIZE_MAX: usize = 64;
 const POLYNOMIAL_DEGREE: usize = 256;
 // const SAMPLE_INTEGER_SIZE: usize = 3;
-const GAMMA1: coefficient::Coefficient = 1 << 19;
-const GAMMA2: coefficient::Coefficient = (Q - 1) / 32;
 const D: coefficient::Coefficient = 13;
-const TAU: usize = 60;
-pub const SIGNATURE_SIZE: usize = SEED_SIZE / 2 + L * POLYZ_PACKED_SIZE + POLYVECH_PACKED_SIZE;
 
-pub fn make_keys<Ctr: Counter>(seed: &Seed) -> Option<(PublicKey, SecretKey)> {
+type Hint<P> = <<P as ParameterSet>::K as Dimension>::Array<[bool; POLYNOMIAL_DEGREE]>;
+/// Challenge seed, response vector `z` and hint of a signature
+type UnpackedSignature<'a, P> = (
+    &'a [u8],
+    Vector<PlainPolynomial, <P as ParameterSet>::L>,
+    Hint<P>,
+);
+
+pub fn make_keys<P: ParameterSet, Ctr: Counter>(
+    seed: &Seed,
+) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
     let mut hasher = Shake256::default();
+    hasher.update(seed);
 
-    let mut rho = [0u8; SEED_SIZE / 2];
-    let mut rho_prime = [0u8; SEED_SIZE];
-    let mut key = [0u8; SEED_SIZE / 2];
+    Ok(make_keys_internal::<P, Ctr>(hasher))
+}
 
-    hasher.update(seed);
+/// Derives the same key pair as [`make_keys`], but with the secret key stored as `seed` only
+pub fn make_compact_keys<P: ParameterSet, Ctr: Counter>(
+    seed: &Seed,
+) -> Result<(PublicKey<P>, CompactSecretKey<P>), Error> {
+    let (pk, _) = make_keys::<P, Ctr>(seed)?;
+
+    Ok((pk, CompactSecretKey::new(seed)))
+}
+
+/// Expands `sk` into the full secret key made by [`make_keys`], which all the signing functions
+/// take
+pub fn expand_secret_key<P: ParameterSet, Ctr: Counter>(sk: &CompactSecretKey<P>) -> SecretKey<P> {
+    let mut hasher = Shake256::default();
+    hasher.update(sk.seed());
+
+    make_keys_internal::<P, Ctr>(hasher).1
+}
+
+/// Derives a key pair from `hasher`, which must have absorbed the seed of the key pair
+fn make_keys_internal<P: ParameterSet, Ctr: Counter>(
+    mut hasher: Shake256,
+) -> (PublicKey<P>, SecretKey<P>) {
+    let mut rho = [0u8; SEED_SIZE / 2];
+    let mut rho_prime = Zeroizing::new([0u8; SEED_SIZE]);
+    let mut key = Zeroizing::new([0u8; SEED_SIZE / 2]);
 
     let mut reader = hasher.finalize_xof_reset();
     reader.read(&mut rho);
-    reader.read(&mut rho_prime);
-    reader.read(&mut key);
+    reader.read(rho_prime.as_mut());
+    reader.read(key.as_mut());
+
+    let s1 = expand::expand_s::<P, P::L>(Ctr::new_wide(&rho_prime), 0);
+    let s2 = expand::expand_s::<P, P::K>(Ctr::new_wide(&rho_prime), P::L::N as u16);
 
-    let a = expand::expand_a(Ctr::new(&rho));
-    let s1 = expand::expand_s::<L>(Ctr::new(subarr!(rho_prime[..HALF_SEED_SIZE])), 0);
-    let s2 = expand::expand_s::<K>(Ctr::new(subarr!(rho_prime[..HALF_SEED_SIZE])), L as u16);
+    pack_keys::<P, Ctr>(&rho, &key, s1, s2)
+}
+
+/// Computes `t` from the secret vectors and packs both keys
+fn pack_keys<P: ParameterSet, Ctr: Counter>(
+    rho: &[u8; SEED_SIZE / 2],
+    key: &[u8; SEED_SIZE / 2],
+    s1: Vector<PlainPolynomial, P::L>,
+    s2: Vector<PlainPolynomial, P::K>,
+) -> (PublicKey<P>, SecretKey<P>) {
+    let a = expand::expand_a::<P>(Ctr::new(rho));
     let (t0, t1) = (make_w(&a, &s1.clone().into_ntt()) + s2.clone()).power2round();
 
-    let pk = pack_public_key(&rho, t1);
+    let pk = pack_public_key::<P>(rho, &t1);
+    let tr = make_tr::<P>(&pk);
+    let sk = make_private_key::<P>(rho, &tr[..P::TR_SIZE], key, &t0, &s1, &s2);
 
-    let mut tr = [0; SEED_SIZE / 2];
+    (pk, sk)
+}
 
-    hasher.update(&pk);
+/// Checks that `sk` holds secret vectors within the bound `ETA`, and the `t0` and `tr` derived
+/// from them, as made by [`make_keys`]
+///
+/// A secret key corrupted in storage still signs, but its signatures never verify.
+pub fn validate_secret_key<P: ParameterSet, Ctr: Counter>(sk: &SecretKey<P>) -> Result<(), Error> {
+    public_key_from_secret_key::<P, Ctr>(sk).map(|_| ())
+}
 
-    let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut tr);
+/// Checks that `sk` is valid as in [`validate_secret_key`] and that `pk` is its public key
+pub fn validate_keypair<P: ParameterSet, Ctr: Counter>(
+    pk: &PublicKey<P>,
+    sk: &SecretKey<P>,
+) -> Result<(), Error> {
+    if public_key_from_secret_key::<P, Ctr>(sk)? == *pk {
+        Ok(())
+    } else {
+        Err(Error::KeyPairMismatch)
+    }
+}
+
+/// Rebuilds the public key of `sk` from `rho`, `s1` and `s2`
+///
+/// The secret key is rebuilt along with it, and the public key is only returned if `tr` and the
+/// rest of the rebuilt secret key match those stored in `sk`.
+pub fn public_key_from_secret_key<P: ParameterSet, Ctr: Counter>(
+    sk: &SecretKey<P>,
+) -> Result<PublicKey<P>, Error> {
+    let [rho, key, _, packed_s1, packed_s2, _] =
+        sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    let s1: Vector<PlainPolynomial, P::L> = unpack_eta::<P, _>(packed_s1);
+    let s2: Vector<PlainPolynomial, P::K> = unpack_eta::<P, _>(packed_s2);
+
+    // The packing of `s1` and `s2` can hold larger coefficients, which would be packed back to the
+    // same bytes
+    if coefficient::max(s1.max(), s2.max()) > P::ETA {
+        return Err(Error::SecretKeyOutOfRange);
+    }
+
+    // Unwrapping is safe here because the slices are of the right size
+    let (pk, expected_sk) =
+        pack_keys::<P, Ctr>(rho.try_into().unwrap(), key.try_into().unwrap(), s1, s2);
+
+    if bytes_eq(expected_sk.as_bytes(), sk.as_bytes()) {
+        Ok(pk)
+    } else {
+        Err(Error::InconsistentSecretKey)
+    }
+}
+
+/// Compares byte strings of the same size in a time that does not depend on where they differ,
+/// since the secret keys compared by [`public_key_from_secret_key`] must not leak
+fn bytes_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    let diff = zip(lhs, rhs).fold(0, |diff, (lhs, rhs)| diff | (lhs ^ rhs));
+
+    black_box(diff) == 0
+}
+
+/// Hashes the public key. Only the first `P::TR_SIZE` bytes of the return value are relevant
+fn make_tr<P: ParameterSet>(pk: &PublicKey<P>) -> [u8; SEED_SIZE] {
+    let mut hasher = Shake256::default();
+    let mut retval = [0u8; SEED_SIZE];
+
+    hasher.update(pk.as_bytes());
 
-    let sk = make_private_key(&rho, &tr, &key, t0, s1, s2)?;
+    let mut reader = hasher.finalize_xof();
+    reader.read(&mut retval[..P::TR_SIZE]);
 
-    Some((pk, sk))
+    retval
 }
 
-fn make_private_key(
+fn make_private_key<P: ParameterSet>(
     rho: &[u8; SEED_SIZE / 2],
-    tr: &[u8; SEED_SIZE / 2],
+    tr: &[u8],
     key: &[u8; SEED_SIZE / 2],
-    t0: Vector<PlainPolynomial, K>,
-    s1: Vector<PlainPolynomial, L>,
-    s2: Vector<PlainPolynomial, K>,
-) -> Option<SecretKey> {
-    let mut retval = [0; SECRET_KEY_SIZE];
-
-    let retval_slices = retval.partition_mut(&[
-        SEED_SIZE / 2,
-        SEED_SIZE / 2,
-        SEED_SIZE / 2,
-        L * ETA_PACKED_SIZE,
-        K * ETA_PACKED_SIZE,
-        K * T0_PACKED_SIZE,
-    ]);
+    t0: &Vector<PlainPolynomial, P::K>,
+    s1: &Vector<PlainPolynomial, P::L>,
+    s2: &Vector<PlainPolynomial, P::K>,
+) -> SecretKey<P> {
+    let mut retval = SecretKey::<P>::zeroed();
+
+    let [retval_rho, retval_key, retval_tr, retval_packed_s1, retval_packed_s2, retval_packed_t0] =
+        retval
+            .as_bytes_mut()
+            .partition_mut(&secret_key_partition_sizes::<P>());
+
+    retval_rho.copy_from_slice(rho);
+    retval_key.copy_from_slice(key);
+    retval_tr.copy_from_slice(tr);
+    pack_eta::<P>(s1, retval_packed_s1);
+    pack_eta::<P>(s2, retval_packed_s2);
+    t0.pack_inplace(&t0_packer, retval_packed_t0);
 
-    let packed_t0: [_; K * T0_PACKED_SIZE] = t0.pack(&t0_packer);
-    let packed_s1: [_; L * ETA_PACKED_SIZE] = s1.pack(&eta_packer);
-    let packed_s2: [_; K * ETA_PACKED_SIZE] = s2.pack(&eta_packer);
+    retval
+}
 
-    retval_slices[0].copy_from_slice(rho);
-    retval_slices[1].copy_from_slice(key);
-    retval_slices[2].copy_from_slice(tr);
-    retval_slices[3].copy_from_slice(&packed_s1);
-    retval_slices[4].copy_from_slice(&packed_s2);
-    retval_slices[5].copy_from_slice(&packed_t0);
+fn public_key_partition_sizes<P: ParameterSet>() -> [usize; 2] {
+    [SEED_SIZE / 2, P::K::N * T1_PACKED_SIZE]
+}
 
-    Some(retval)
+fn secret_key_partition_sizes<P: ParameterSet>() -> [usize; 6] {
+    [
+        SEED_SIZE / 2,
+        SEED_SIZE / 2,
+        P::TR_SIZE,
+        P::L::N * P::ETA_PACKED_SIZE,
+        P::K::N * P::ETA_PACKED_SIZE,
+        P::K::N * T0_PACKED_SIZE,
+    ]
 }
 
 fn t0_packer(chunk: &[Coefficient; 8]) -> [u8; 13] {
@@ -150,8 +277,17 @@ fn t0_packer(chunk: &[Coefficient; 8]) -> [u8; 13] {
     .map(|coeff| coeff as u8)
 }
 
-fn eta_packer(chunk: &[Coefficient; 8]) -> [u8; 3] {
-    let chunk = chunk.map(|coeff| ETA - coeff);
+/// Packs a polynomial or a vector of polynomials whose coefficients are within the bound `ETA`
+fn pack_eta<P: ParameterSet>(s: &impl Pack<Input = Coefficient, Output = u8>, output: &mut [u8]) {
+    if P::ETA == 2 {
+        s.pack_inplace(&eta_packer_3bits, output);
+    } else {
+        s.pack_inplace(&eta_packer_4bits, output);
+    }
+}
+
+fn eta_packer_3bits(chunk: &[Coefficient; 8]) -> [u8; 3] {
+    let chunk = chunk.map(|coeff| 2 - coeff);
 
     [
         chunk[0] | (chunk[1] << 3) | (chunk[2] << 6),
@@ -161,35 +297,87 @@ fn eta_packer(chunk: &[Coefficient; 8]) -> [u8; 3] {
     .map(|coeff| coeff as u8)
 }
 
-fn pack_public_key(rho: &[u8; SEED_SIZE / 2], t1: Vector<PlainPolynomial, K>) -> PublicKey {
-    let packed_t1: [_; K * T1_PACKED_SIZE] = t1.pack(&|chunk: &[Coefficient; 4]| {
-        [
-            chunk[0],
-            chunk[0] >> 8 | chunk[1] << 2,
-            chunk[1] >> 6 | chunk[2] << 4,
-            chunk[2] >> 4 | chunk[3] << 6,
-            chunk[3] >> 2,
-        ]
-        .map(|n| n as u8)
-    });
+fn eta_packer_4bits(chunk: &[Coefficient; 2]) -> [u8; 1] {
+    let chunk = chunk.map(|coeff| 4 - coeff);
 
-    let mut retval = [0; PUBLIC_KEY_SIZE];
+    [(chunk[0] | (chunk[1] << 4)) as u8]
+}
 
-    retval[..SEED_SIZE / 2].copy_from_slice(rho);
-    retval[SEED_SIZE / 2..].copy_from_slice(&packed_t1);
+fn pack_public_key<P: ParameterSet>(
+    rho: &[u8; SEED_SIZE / 2],
+    t1: &Vector<PlainPolynomial, P::K>,
+) -> PublicKey<P> {
+    let mut retval = PublicKey::<P>::zeroed();
+
+    let [retval_rho, retval_packed_t1] = retval
+        .as_bytes_mut()
+        .partition_mut(&public_key_partition_sizes::<P>());
+
+    retval_rho.copy_from_slice(rho);
+    t1.pack_inplace(&t1_packer, retval_packed_t1);
 
     retval
 }
 
-fn make_w(
+fn t1_packer(chunk: &[Coefficient; 4]) -> [u8; 5] {
+    [
+        chunk[0],
+        chunk[0] >> 8 | chunk[1] << 2,
+        chunk[1] >> 6 | chunk[2] << 4,
+        chunk[2] >> 4 | chunk[3] << 6,
+        chunk[3] >> 2,
+    ]
+    .map(|n| n as u8)
+}
+
+fn make_w<K: Dimension, L: Dimension>(
     a: &Matrix<NTTPolynomial, L, K>,
     y: &Vector<NTTPolynomial, L>,
 ) -> Vector<PlainPolynomial, K> {
     (a * y).reduce_32().into_plain().caddq()
 }
 
-pub fn make_challenge(seed: &[u8; SEED_SIZE / 2]) -> PlainPolynomial {
-    const FIRST_TAU_BITS_MASK: u64 = (1 << TAU) - 1;
+fn update_with_w1<P: ParameterSet>(hasher: &mut Shake256, w1: &Vector<PlainPolynomial, P::K>) {
+    let mut packed_w1_buf = [0u8; POLYW1_PACKED_SIZE_MAX];
+    let packed_w1 = &mut packed_w1_buf[..P::POLYW1_PACKED_SIZE];
+
+    // Polynomials are packed and absorbed one after the other, which is equivalent to absorbing
+    // the whole packed vector at once
+    for poly in w1 {
+        update_with_w1_polynomial::<P>(hasher, poly, packed_w1);
+    }
+}
+
+/// Absorbs a single polynomial of `w1`, packed in `packed_w1`
+fn update_with_w1_polynomial<P: ParameterSet>(
+    hasher: &mut Shake256,
+    poly: &PlainPolynomial,
+    packed_w1: &mut [u8],
+) {
+    if P::GAMMA2 == (Q - 1) / 88 {
+        poly.pack_inplace(&w1_packer_6bits, packed_w1);
+    } else {
+        poly.pack_inplace(&w1_packer_4bits, packed_w1);
+    }
+
+    hasher.update(packed_w1);
+}
+
+fn w1_packer_4bits(chunk: &[Coefficient; 2]) -> [u8; 1] {
+    [(chunk[0] | (chunk[1] << 4)) as u8]
+}
+
+fn w1_packer_6bits(chunk: &[Coefficient; 4]) -> [u8; 3] {
+    [
+        chunk[0] | (chunk[1] << 6),
+        (chunk[1] >> 2) | (chunk[2] << 4),
+        (chunk[2] >> 4) | (chunk[3] << 2),
+    ]
+    .map(|n| n as u8)
+}
+
+pub fn make_challenge<P: ParameterSet>(seed: &[u8]) -> PlainPolynomial {
+    let first_tau_bits_mask: u64 = (1 << P::TAU) - 1;
 
     let mut hasher = Shake256::default();
     let mut sign_bits_buf = [0u8; size_of::<u64>()];
@@ -200,9 +388,9 @@ pub fn make_challenge(seed: &[u8; SEED_SIZE / 2]) -> PlainPolynomial {
     let mut reader = hasher.finalize_xof();
     reader.read(&mut sign_bits_buf);
 
-    let mut sign_bits = u64::from_le_bytes(sign_bits_buf) & FIRST_TAU_BITS_MASK;
+    let mut sign_bits = u64::from_le_bytes(sign_bits_buf) & first_tau_bits_mask;
 
-    for last_bit_index in POLYNOMIAL_DEGREE - TAU..POLYNOMIAL_DEGREE {
+    for last_bit_index in POLYNOMIAL_DEGREE - P::TAU..POLYNOMIAL_DEGREE {
         let chosen_bit_index = (0..)
             .map(|_| {
                 let mut buf = [0u8; 1];
@@ -220,163 +408,369 @@ pub fn make_challenge(seed: &[u8; SEED_SIZE / 2]) -> PlainPolynomial {
     PlainPolynomial::from(retval)
 }
 
-pub fn sign<Ctr: Counter>(msg: &[u8], sk: &SecretKey) -> Signature {
-    #[inline(never)]
-    fn compute_ss_and_t(
-        packed_s1: &[u8],
-        packed_s2: &[u8],
-        packed_t0: &[u8],
-    ) -> (
-        Vector<NTTPolynomial, L>,
-        Vector<NTTPolynomial, K>,
-        Vector<NTTPolynomial, K>,
-    ) {
-        let s1: Vector<PlainPolynomial, L> = Pack::unpack(packed_s1, &eta_unpacker);
-        let s2: Vector<PlainPolynomial, K> = Pack::unpack(packed_s2, &eta_unpacker);
-        let t0: Vector<PlainPolynomial, K> = Pack::unpack(packed_t0, &t0_unpacker);
+pub fn sign<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
 
-        (s1.into_ntt(), s2.into_ntt(), t0.into_ntt())
-    }
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), &[], sk))
+}
 
-    let [rho, key, tr, packed_s1, packed_s2, packed_t0] = sk.partition(&[
-        SEED_SIZE / 2,
-        SEED_SIZE / 2,
-        SEED_SIZE / 2,
-        L * ETA_PACKED_SIZE,
-        K * ETA_PACKED_SIZE,
-        K * T0_PACKED_SIZE,
-    ]);
+/// Signs `msg` in the hedged variant, where `rnd` must be fresh randomness
+///
+/// Unlike [`sign`], the seed of the masking vector does not only depend on the key and the
+/// message, which hardens the signer against fault and side-channel attacks. Passing the
+/// randomness explicitly keeps the signature reproducible for test vectors.
+pub fn sign_hedged<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), rnd, sk))
+}
 
-    let (s1, s2, t0) = compute_ss_and_t(packed_s1, packed_s2, packed_t0);
+/// Signs `msg` in the hedged variant with randomness drawn from `rng`
+pub fn sign_with_rng<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Signature<P>, Error> {
+    let mut rnd = Zeroizing::new([0u8; RANDOMNESS_SIZE]);
+    rng.fill_bytes(rnd.as_mut());
 
+    sign_hedged::<P, Ctr>(msg, sk, &rnd)
+}
+
+/// Signs `msg` with a compact secret key, which yields the same signature as [`sign`]
+///
+/// The full secret key is expanded from the seed for the duration of the call, and wiped
+/// afterwards.
+pub fn sign_compact<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &CompactSecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    sign::<P, Ctr>(msg, &expand_secret_key::<P, Ctr>(sk))
+}
+
+/// Signs `msg` with a compact secret key in the hedged variant, which yields the same signature as
+/// [`sign_hedged`]
+pub fn sign_compact_hedged<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &CompactSecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    sign_hedged::<P, Ctr>(msg, &expand_secret_key::<P, Ctr>(sk), rnd)
+}
+
+/// Signs `msg` as [`sign`] does, and writes the signed message `signature || msg` to `sm`
+///
+/// This is the format of `crypto_sign` in the reference implementation and of the `sm` field of
+/// the NIST known-answer tests. Fails with [`Error::BufferTooSmall`] if `sm` is smaller than
+/// `P::SIGNATURE_SIZE + msg.len()`.
+pub fn sign_attached<'a, P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    sm: &'a mut [u8],
+) -> Result<&'a [u8], Error> {
+    let sm = signed_message_buffer::<P>(sm, msg.len())?;
+    let signature = sign::<P, Ctr>(msg, sk)?;
+
+    sm[..P::SIGNATURE_SIZE].copy_from_slice(signature.as_bytes());
+    sm[P::SIGNATURE_SIZE..].copy_from_slice(msg);
+
+    Ok(sm)
+}
+
+/// Signs the message held by the first `msg_len` bytes of `buffer`, and turns `buffer` into the
+/// signed message of [`sign_attached`]
+///
+/// The message is moved after the signature, so no second buffer is needed.
+pub fn sign_attached_in_place<'a, P: ParameterSet, Ctr: Counter>(
+    buffer: &'a mut [u8],
+    msg_len: usize,
+    sk: &SecretKey<P>,
+) -> Result<&'a [u8], Error> {
+    let sm = signed_message_buffer::<P>(buffer, msg_len)?;
+    let signature = sign::<P, Ctr>(&sm[..msg_len], sk)?;
+
+    sm.copy_within(..msg_len, P::SIGNATURE_SIZE);
+    sm[..P::SIGNATURE_SIZE].copy_from_slice(signature.as_bytes());
+
+    Ok(sm)
+}
+
+/// Returns the beginning of `buffer` that holds the signed message of `msg_len` bytes
+fn signed_message_buffer<P: ParameterSet>(
+    buffer: &mut [u8],
+    msg_len: usize,
+) -> Result<&mut [u8], Error> {
+    let actual = buffer.len();
+    // No buffer is large enough for a size that overflows
+    let needed = P::SIGNATURE_SIZE
+        .checked_add(msg_len)
+        .ok_or(Error::BufferTooSmall {
+            needed: usize::MAX,
+            actual,
+        })?;
+
+    buffer
+        .get_mut(..needed)
+        .ok_or(Error::BufferTooSmall { needed, actual })
+}
+
+/// Computes the message representative `mu` that [`sign_mu`] and [`verify_mu`] take in place of
+/// the message
+///
+/// Only the public key is needed, so `mu` can be computed next to the data while the secret key
+/// stays on a constrained or remote signer.
+pub fn make_mu<P: ParameterSet>(msg: &[u8], pk: &PublicKey<P>) -> Mu {
+    let tr = make_tr::<P>(pk);
+
+    hash_message(&tr[..P::TR_SIZE], msg)
+}
+
+/// Signs a message given by its representative `mu`, which yields the same signature as [`sign`]
+pub fn sign_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, &[], sk))
+}
+
+/// Signs a message given by its representative `mu` in the hedged variant, which yields the same
+/// signature as [`sign_hedged`]
+pub fn sign_mu_hedged<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, rnd, sk))
+}
+
+/// Signs the concatenation of `parts` as [`sign`] signs a message, or as [`sign_hedged`] does when
+/// `rnd` is given, without copying them
+#[cfg(any(feature = "cose", feature = "jose"))]
+pub(crate) fn sign_parts<P: ParameterSet, Ctr: Counter>(
+    parts: &[&[u8]],
+    sk: &SecretKey<P>,
+    rnd: Option<&[u8; RANDOMNESS_SIZE]>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let rnd = rnd.map_or(&[][..], |rnd| rnd);
+
+    Ok(sign_internal::<P, Ctr>(
+        &hash_message_parts(tr, parts),
+        rnd,
+        sk,
+    ))
+}
+
+/// Verifies `signature` against the concatenation of `parts` as [`verify`] does against a message,
+/// without copying them
+#[cfg(any(feature = "cose", feature = "jose"))]
+pub(crate) fn verify_parts<P: ParameterSet, Ctr: Counter>(
+    parts: &[&[u8]],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    let tr = make_tr::<P>(pk);
+
+    verify_internal::<P, Ctr>(&hash_message_parts(&tr[..P::TR_SIZE], parts), signature, pk)
+}
+
+fn hash_message(tr: &[u8], msg: &[u8]) -> Mu {
+    hash_message_parts(tr, &[msg])
+}
+
+fn hash_message_parts(tr: &[u8], parts: &[&[u8]]) -> Mu {
     let mut hasher = Shake256::default();
+    let mut retval = [0u8; SEED_SIZE];
+
     hasher.update(tr);
-    hasher.update(msg);
+    for part in parts {
+        hasher.update(part);
+    }
 
-    let mut mu = [0u8; SEED_SIZE];
-    let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut mu);
+    let mut reader = hasher.finalize_xof();
+    reader.read(&mut retval);
 
-    let mut rho_prime = [0u8; SEED_SIZE];
+    retval
+}
 
-    hasher.update(key);
-    hasher.update(&mu);
+/// Signs the message representative `mu`. `rnd` is absorbed between the key and `mu` when
+/// deriving the seed of the masking vector, and is empty for the deterministic variant.
+fn sign_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    rnd: &[u8],
+    sk: &SecretKey<P>,
+) -> Signature<P> {
+    let [rho, key, _, packed_s1, packed_s2, packed_t0] =
+        sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
 
-    let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut rho_prime);
+    let (s1, s2, t0) = compute_ss_and_t::<P>(packed_s1, packed_s2, packed_t0);
 
     // Unwrapping is safe here because the slice is of the right size
-    let a = expand::expand_a(Ctr::new(rho.as_ref().try_into().unwrap()));
+    let a = expand::expand_a::<P>(Ctr::new(rho.try_into().unwrap()));
+
+    sign_expanded_internal::<P, Ctr>(mu, rnd, key, &a, &s1, &s2, &t0)
+}
+
+#[inline(never)]
+#[allow(clippy::type_complexity)]
+fn compute_ss_and_t<P: ParameterSet>(
+    packed_s1: &[u8],
+    packed_s2: &[u8],
+    packed_t0: &[u8],
+) -> (
+    Vector<NTTPolynomial, P::L>,
+    Vector<NTTPolynomial, P::K>,
+    Vector<NTTPolynomial, P::K>,
+) {
+    let s1: Vector<PlainPolynomial, P::L> = unpack_eta::<P, _>(packed_s1);
+    let s2: Vector<PlainPolynomial, P::K> = unpack_eta::<P, _>(packed_s2);
+    let t0: Vector<PlainPolynomial, P::K> = Pack::unpack(packed_t0, &t0_unpacker);
+
+    (s1.into_ntt(), s2.into_ntt(), t0.into_ntt())
+}
+
+/// Signs `mu` as [`sign_internal`] does, with the matrix and the secret vectors already expanded
+fn sign_expanded_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    rnd: &[u8],
+    key: &[u8],
+    a: &Matrix<NTTPolynomial, P::L, P::K>,
+    s1: &Vector<NTTPolynomial, P::L>,
+    s2: &Vector<NTTPolynomial, P::K>,
+    t0: &Vector<NTTPolynomial, P::K>,
+) -> Signature<P> {
+    let rho_prime = make_rho_prime(key, rnd, mu);
 
     // Will not panic since input iterator is infinite
-    let sample = |nonce| sample_signature::<Ctr>(nonce, &rho_prime, &a, &mu, &s1, &s2, &t0);
+    let sample = |nonce| sample_signature::<P, Ctr>(nonce, &rho_prime, a, mu, s1, s2, t0);
     (0..).find_map(sample).unwrap()
 }
 
+/// Derives the seed of the masking vector, where `rnd` is absorbed between the key and `mu`
+fn make_rho_prime(key: &[u8], rnd: &[u8], mu: &Mu) -> Zeroizing<[u8; SEED_SIZE]> {
+    let mut hasher = Shake256::default();
+    let mut retval = Zeroizing::new([0u8; SEED_SIZE]);
+
+    hasher.update(key);
+    hasher.update(rnd);
+    hasher.update(mu);
+
+    let mut reader = hasher.finalize_xof_reset();
+    reader.read(retval.as_mut());
+
+    retval
+}
+
 #[inline(never)]
-fn sample_signature<Ctr: Counter>(
+fn sample_signature<P: ParameterSet, Ctr: Counter>(
     nonce: u16,
     rho_prime: &[u8; SEED_SIZE],
-    a: &Matrix<NTTPolynomial, L, K>,
+    a: &Matrix<NTTPolynomial, P::L, P::K>,
     mu: &[u8; SEED_SIZE],
-    s1: &Vector<NTTPolynomial, L>,
-    s2: &Vector<NTTPolynomial, K>,
-    t0: &Vector<NTTPolynomial, K>,
-) -> Option<Signature> {
+    s1: &Vector<NTTPolynomial, P::L>,
+    s2: &Vector<NTTPolynomial, P::K>,
+    t0: &Vector<NTTPolynomial, P::K>,
+) -> Option<Signature<P>> {
     let mut hasher = Shake256::default();
 
-    let y = expand::expand_y(Ctr::new(subarr!(rho_prime[..HALF_SEED_SIZE])), nonce);
-    let (w0, w1) = make_w(a, &y.clone().into_ntt()).decompose();
-    let mut challenge_seed = [0u8; SEED_SIZE / 2];
-
-    let packed_w1: [_; K * NB_COEFFICIENTS / 2] =
-        w1.pack(&|chunk: &[Coefficient; 2]| [(chunk[0] | (chunk[1] << 4)) as u8]);
+    let y = expand::expand_y::<P>(Ctr::new_wide(rho_prime), nonce);
+    let (w0, w1) = make_w(a, &y.clone().into_ntt()).decompose::<P>();
+    let mut challenge_seed_buf = [0u8; CHALLENGE_SEED_SIZE_MAX];
+    let challenge_seed = &mut challenge_seed_buf[..P::CHALLENGE_SEED_SIZE];
 
     hasher.update(mu);
-    hasher.update(&packed_w1);
+    update_with_w1::<P>(&mut hasher, &w1);
 
     let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut challenge_seed);
+    reader.read(challenge_seed);
 
-    let challenge = make_challenge(&challenge_seed).into_ntt();
+    let challenge = make_challenge::<P>(challenge_seed).into_ntt();
     let z = ((s1.clone() * &challenge).into_plain() + y).reduce_32();
-    if z.max() >= GAMMA1 - BETA {
+    if z.max() >= P::GAMMA1 - P::BETA {
         return None;
     }
 
     let cs2 = (w0 - (s2.clone() * &challenge).into_plain()).reduce_32();
-    if cs2.max() >= GAMMA2 - BETA {
+    if cs2.max() >= P::GAMMA2 - P::BETA {
         return None;
     }
 
     let ct = (t0.clone() * &challenge).into_plain().reduce_32();
-    if ct.max() >= GAMMA2 - BETA {
+    if ct.max() >= P::GAMMA2 {
         return None;
     }
 
-    let (hint, hint_bits_count) = make_hint(cs2, ct, &w1);
-    if hint_bits_count > OMEGA {
+    let (hint, hint_bits_count) = make_hint::<P, _>(cs2, ct, &w1);
+    if hint_bits_count > P::OMEGA {
         return None;
     }
 
-    Some(make_signature(&challenge_seed, &z, &hint))
+    Some(make_signature::<P>(challenge_seed, &z, &hint))
 }
 
-fn make_signature(
-    challenge_seed: &[u8; SEED_SIZE / 2],
-    z: &Vector<PlainPolynomial, L>,
-    hint: &[[bool; POLYNOMIAL_DEGREE]; K],
-) -> Signature {
-    fn pack_hint_polynomial(
-        poly: &[bool; POLYNOMIAL_DEGREE],
-    ) -> (impl Iterator<Item = u8> + '_, u8) {
-        let (it1, it2) = poly.iter().tee();
-        (
-            it1.enumerate().filter(|(_, &b)| b).map(|(i, _)| i as u8),
-            it2.filter(|&&b| b).count() as u8,
-        )
-    }
+fn make_signature<P: ParameterSet>(
+    challenge_seed: &[u8],
+    z: &Vector<PlainPolynomial, P::L>,
+    hint: &Hint<P>,
+) -> Signature<P> {
+    let mut retval = Signature::<P>::zeroed();
 
-    let mut retval = [0; SIGNATURE_SIZE];
+    let [retval_challenge_seed, retval_packed_z, retval_packed_hint] = retval
+        .as_bytes_mut()
+        .partition_mut(&signature_partition_sizes::<P>());
 
-    let (hint_positions, hint_numbers_by_polynomial): ([_; K], _) = hint
-        .iter()
-        .map(pack_hint_polynomial)
-        .unzip_into_arrays()
-        .unwrap();
+    retval_challenge_seed.copy_from_slice(challenge_seed);
+    pack_z::<P>(z, retval_packed_z);
+    pack_hint::<P>(hint, retval_packed_hint);
 
-    let [retval_challenge_seed, retval_packed_z, retval_packed_hint] =
-        retval.partition_mut(&[SEED_SIZE / 2, L * POLYZ_PACKED_SIZE, POLYVECH_PACKED_SIZE]);
+    retval
+}
 
-    retval_challenge_seed.copy_from_slice(challenge_seed);
+fn signature_partition_sizes<P: ParameterSet>() -> [usize; 3] {
+    [
+        P::CHALLENGE_SEED_SIZE,
+        P::L::N * P::POLYZ_PACKED_SIZE,
+        P::POLYVECH_PACKED_SIZE,
+    ]
+}
 
-    let packed_z: [_; L * POLYZ_PACKED_SIZE] = z.pack(&z_packer);
-    retval_packed_z.copy_from_slice(&packed_z);
+fn pack_hint<P: ParameterSet>(hint: &Hint<P>, output: &mut [u8]) {
+    let mut hint_bits_count = 0;
 
-    for (lhs, rhs) in zip(
-        retval_packed_hint.iter_mut(),
-        hint_positions.into_iter().flatten(),
-    ) {
-        *lhs = rhs;
+    for (i, poly) in hint.as_ref().iter().enumerate() {
+        hint_bits_count = pack_hint_polynomial::<P>(poly, i, hint_bits_count, output);
     }
+}
 
-    let accumulated_hint_numbers: [_; K] = hint_numbers_by_polynomial
-        .into_iter()
-        .scan(0, |acc, n| {
-            *acc += n;
-            Some(*acc)
-        })
-        .try_collect_array()
-        .unwrap();
-
-    for (lhs, rhs) in zip(
-        retval_packed_hint.iter_mut().rev(),
-        accumulated_hint_numbers.into_iter().rev(),
-    ) {
-        *lhs = rhs;
+/// Packs the polynomial `i` of a hint into `output`, after the `hint_bits_count` ones of the
+/// previous polynomials, and returns the number of ones up to this polynomial
+fn pack_hint_polynomial<P: ParameterSet>(
+    poly: &[bool; POLYNOMIAL_DEGREE],
+    i: usize,
+    hint_bits_count: usize,
+    output: &mut [u8],
+) -> usize {
+    // The first `OMEGA` bytes hold the positions of the ones in each polynomial of the hint, and
+    // the last `K` bytes hold the accumulated number of ones after each polynomial
+    let (hint_positions, hint_numbers) = output.split_at_mut(P::OMEGA);
+    let positions_it = poly.iter().enumerate().filter(|(_, &b)| b);
+    let mut accumulated_hint_number = hint_bits_count;
+
+    for ((j, _), lhs) in zip(positions_it, &mut hint_positions[hint_bits_count..]) {
+        *lhs = j as u8;
+        accumulated_hint_number += 1;
     }
 
-    retval
+    hint_numbers[i] = accumulated_hint_number as u8;
+
+    accumulated_hint_number
 }
 
 trait Tee {
@@ -393,32 +787,33 @@ impl<Iter: Iterator + Clone> Tee for Iter {
     }
 }
 
-trait UnzipIntoArrays<T1, T2> {
-    fn unzip_into_arrays<const N: usize>(self) -> Option<([T1; N], [T2; N])>;
+/// Packs a polynomial or a vector of polynomials of the response vector `z`
+fn pack_z<P: ParameterSet>(z: &impl Pack<Input = Coefficient, Output = u8>, output: &mut [u8]) {
+    if P::GAMMA1 == 1 << 17 {
+        z.pack_inplace(&z_packer_18bits, output);
+    } else {
+        z.pack_inplace(&z_packer_20bits, output);
+    }
 }
 
-impl<T: Iterator<Item = (T1, T2)>, T1, T2> UnzipIntoArrays<T1, T2> for T {
-    fn unzip_into_arrays<const N: usize>(mut self) -> Option<([T1; N], [T2; N])> {
-        let mut retval1: [_; N] = from_fn(|_| MaybeUninit::uninit());
-        let mut retval2: [_; N] = from_fn(|_| MaybeUninit::uninit());
-
-        for (lhs1, lhs2) in zip(retval1.iter_mut(), retval2.iter_mut()) {
-            let (rhs1, rhs2) = self.next()?;
-            lhs1.write(rhs1);
-            lhs2.write(rhs2);
-        }
-
-        unsafe {
-            Some((
-                retval1.as_ptr().cast::<[T1; N]>().read(),
-                retval2.as_ptr().cast::<[T2; N]>().read(),
-            ))
-        }
-    }
+fn z_packer_18bits(chunk: &[Coefficient; 4]) -> [u8; 9] {
+    let chunk = chunk.map(|coeff| (1 << 17) - coeff);
+    [
+        chunk[0],
+        chunk[0] >> 8,
+        chunk[0] >> 16 | chunk[1] << 2,
+        chunk[1] >> 6,
+        chunk[1] >> 14 | chunk[2] << 4,
+        chunk[2] >> 4,
+        chunk[2] >> 12 | chunk[3] << 6,
+        chunk[3] >> 2,
+        chunk[3] >> 10,
+    ]
+    .map(|coeff| coeff as u8)
 }
 
-fn z_packer(chunk: &[Coefficient; 2]) -> [u8; 5] {
-    let chunk = chunk.map(|coeff| GAMMA1 - coeff);
+fn z_packer_20bits(chunk: &[Coefficient; 2]) -> [u8; 5] {
+    let chunk = chunk.map(|coeff| (1 << 19) - coeff);
     [
         chunk[0],
         chunk[0] >> 8,
@@ -429,25 +824,36 @@ fn z_packer(chunk: &[Coefficient; 2]) -> [u8; 5] {
     .map(|coeff| coeff as u8)
 }
 
-fn make_hint(
+fn make_hint<P: ParameterSet, K: Dimension>(
     cs2: Vector<PlainPolynomial, K>,
     ct: Vector<PlainPolynomial, K>,
     w1: &Vector<PlainPolynomial, K>,
-) -> ([[bool; POLYNOMIAL_DEGREE]; K], usize) {
-    let w0 = cs2 + ct;
+) -> (K::Array<[bool; POLYNOMIAL_DEGREE]>, usize) {
+    let mut hint = K::try_from_fn(|_| Some([false; POLYNOMIAL_DEGREE])).unwrap();
+    let hint_bits_count = make_hint_into::<P, K>(&(cs2 + ct), w1, &mut hint);
+
+    (hint, hint_bits_count)
+}
 
+/// Writes the hint of `w0 = cs2 + ct` and `w1` to `hint`, and returns its number of ones
+fn make_hint_into<P: ParameterSet, K: Dimension>(
+    w0: &Vector<PlainPolynomial, K>,
+    w1: &Vector<PlainPolynomial, K>,
+    hint: &mut K::Array<[bool; POLYNOMIAL_DEGREE]>,
+) -> usize {
     let w0_it = w0.into_iter().flatten();
     let w1_it = w1.into_iter().flatten();
 
     let (hint_it, count_it) = zip(w0_it, w1_it)
-        .map(|(&coeff0, &coeff1)| {
-            !(-GAMMA2..=GAMMA2).contains(&coeff0) || coeff0 == -GAMMA2 && coeff1 != 0
-        })
+        .map(|(&coeff0, &coeff1)| coefficient::make_hint::<P>(coeff0, coeff1))
         .tee();
 
-    let hint = hint_it._array_chunks().try_collect_array().unwrap();
+    let hint_chunk_it = hint_it.map(|bit| bit != 0)._array_chunks();
+    for (poly, hint_chunk) in zip(hint.as_mut(), hint_chunk_it) {
+        *poly = hint_chunk;
+    }
 
-    (hint, count_it.filter(|&b| b).count())
+    count_it.sum::<Coefficient>() as usize
 }
 
 trait Partition {
@@ -466,23 +872,16 @@ impl<'a> Partition for &'a [u8] {
     type Slice = &'a [u8];
 
     fn partition<const N: usize>(self, sizes: &[usize; N]) -> [Self::Slice; N] {
-        assert!(sizes.iter().all(|&size| size > 0));
+        // Only the sizes are checked, which are public even when the slice holds a secret key
         assert!(sizes.iter().sum::<usize>() == self.len());
 
-        let mut it = sizes.iter().chain(once(&0));
-        let mut counter = *it.next().unwrap();
-        let mut retval_it = self.split_inclusive(|_| {
-            counter -= 1;
-            if counter == 0 {
-                counter = *it.next().unwrap();
-                true
-            } else {
-                false
-            }
-        });
+        let mut remainder = self;
 
-        // Should never panic since `sizes` is as large as the return value
-        from_fn(|_| retval_it.next().unwrap())
+        from_fn(|i| {
+            let (retval, tail) = remainder.split_at(sizes[i]);
+            remainder = tail;
+            retval
+        })
     }
 }
 
@@ -498,27 +897,27 @@ impl<'a> PartitionMut for &'a mut [u8] {
     type SliceMut = &'a mut [u8];
 
     fn partition_mut<const N: usize>(self, sizes: &[usize; N]) -> [Self::SliceMut; N] {
-        assert!(sizes.iter().all(|&size| size > 0));
         assert!(sizes.iter().sum::<usize>() == self.len());
 
-        let mut it = sizes.iter().chain(once(&0));
-        let mut counter = *it.next().unwrap();
-        let mut retval_it = self.split_inclusive_mut(|_| {
-            counter -= 1;
-            if counter == 0 {
-                counter = *it.next().unwrap();
-                true
-            } else {
-                false
-            }
-        });
+        let mut remainder = self;
 
-        // Should never panic since `sizes` is as large as the return value
-        from_fn(|_| retval_it.next().unwrap())
+        from_fn(|i| {
+            let (retval, tail) = take(&mut remainder).split_at_mut(sizes[i]);
+            remainder = tail;
+            retval
+        })
+    }
+}
+
+fn unpack_eta<P: ParameterSet, T: Pack<Input = Coefficient, Output = u8>>(packed: &[u8]) -> T {
+    if P::ETA == 2 {
+        Pack::unpack(packed, &eta_unpacker_3bits)
+    } else {
+        Pack::unpack(packed, &eta_unpacker_4bits)
     }
 }
 
-fn eta_unpacker(chunk: &[u8; 3]) -> [Coefficient; 8] {
+fn eta_unpacker_3bits(chunk: &[u8; 3]) -> [Coefficient; 8] {
     const _3BITS_MASK: coefficient::Coefficient = 7;
 
     [
@@ -532,7 +931,15 @@ fn eta_unpacker(chunk: &[u8; 3]) -> [Coefficient; 8] {
         chunk[2] >> 5,
     ]
     .map(|n| n as Coefficient)
-    .map(|n| ETA - (n & _3BITS_MASK))
+    .map(|n| 2 - (n & _3BITS_MASK))
+}
+
+fn eta_unpacker_4bits(chunk: &[u8; 1]) -> [Coefficient; 2] {
+    const _4BITS_MASK: coefficient::Coefficient = 15;
+
+    [chunk[0], chunk[0] >> 4]
+        .map(|n| n as Coefficient)
+        .map(|n| 4 - (n & _4BITS_MASK))
 }
 
 fn t0_unpacker(chunk: &[u8; 13]) -> [Coefficient; 8] {
@@ -554,86 +961,158 @@ fn t0_unpacker(chunk: &[u8; 13]) -> [Coefficient; 8] {
     .map(|n| (DBITS_MASK / 2 + 1) - (n & DBITS_MASK))
 }
 
-pub fn verify<Ctr: Counter>(msg: &[u8], signature: &Signature, pk: &PublicKey) -> bool {
-    let mut hasher = Shake256::default();
+pub fn verify<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(&make_mu::<P>(msg, pk), signature, pk)
+}
 
-    let [rho, packed_t1] = pk.partition(&[SEED_SIZE / 2, K * T1_PACKED_SIZE]);
-    let t1: Vector<PlainPolynomial, K> = Pack::unpack(packed_t1, &t1_unpacker);
+/// Verifies `signature` against a message given by its representative `mu`
+pub fn verify_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(mu, signature, pk)
+}
 
-    let [expected_challenge_seed, packed_z, packed_hint] =
-        signature.partition(&[SEED_SIZE / 2, L * POLYZ_PACKED_SIZE, POLYVECH_PACKED_SIZE]);
-    let z: Vector<PlainPolynomial, L> = Pack::unpack(packed_z, &z_unpacker);
-    if z.max() >= GAMMA1 - BETA {
-        return false;
+/// Verifies the signed message `sm` made by [`sign_attached`], and returns its message
+///
+/// This is `crypto_sign_open` of the reference implementation, except that the message is
+/// borrowed from `sm` rather than copied.
+pub fn open_attached<'a, P: ParameterSet, Ctr: Counter>(
+    sm: &'a [u8],
+    pk: &PublicKey<P>,
+) -> Result<&'a [u8], Error> {
+    if sm.len() < P::SIGNATURE_SIZE {
+        return Err(Error::WrongLength {
+            expected: P::SIGNATURE_SIZE,
+            actual: sm.len(),
+        });
     }
 
-    let hint = if let Some(hint) = unpack_hint(packed_hint.try_into().unwrap()) {
-        hint
-    } else {
-        return false;
-    };
+    let (signature, msg) = sm.split_at(P::SIGNATURE_SIZE);
+    verify::<P, Ctr>(msg, &Signature::from_bytes(signature)?, pk)?;
 
-    let mut mu = [0u8; SEED_SIZE / 2];
-    hasher.update(pk);
+    Ok(msg)
+}
 
-    let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut mu);
+/// Verifies each signature of `items` against its message, expanding the public key only once
+///
+/// The results are yielded in the order of `items`, as [`verify`] would return them.
+pub fn verify_batch<'a, P: ParameterSet, Ctr: Counter + 'a>(
+    items: &'a [(&'a [u8], Signature<P>)],
+    pk: &PublicKey<P>,
+) -> impl Iterator<Item = Result<(), Error>> + 'a {
+    let expanded_pk = ExpandedVerifyingKey::<P, Ctr>::new(pk);
 
-    hasher.update(&mu);
-    hasher.update(msg);
+    items
+        .iter()
+        .map(move |(msg, signature)| expanded_pk.verify(msg, signature))
+}
 
-    let mut mu = [0u8; SEED_SIZE];
-    let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut mu);
+/// Verifies `signature` against the message representative `mu`
+fn verify_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    let [rho, packed_t1] = pk.as_ref().partition(&public_key_partition_sizes::<P>());
+    let t1: Vector<PlainPolynomial, P::K> = Pack::unpack(packed_t1, &t1_unpacker);
+
+    // The signature is unpacked first so that malformed signatures are rejected before the
+    // expansion of the matrix
+    let unpacked_signature = unpack_signature::<P>(signature.as_bytes())?;
+    let a = expand::expand_a::<P>(Ctr::new(rho.try_into().unwrap()));
+
+    verify_expanded_internal::<P>(mu, unpacked_signature, &a, t1.shift_d().into_ntt())
+}
 
-    let challenge = make_challenge(expected_challenge_seed.try_into().unwrap()).into_ntt();
-    let a = expand::expand_a(Ctr::new(rho.try_into().unwrap()));
-    let w1 = &a * &z.into_ntt();
-    let t1 = t1.shift_d().into_ntt() * &challenge;
-    let w1 = (w1 - t1).reduce_32().into_plain().caddq().use_hint(&hint);
+/// Verifies an unpacked signature as [`verify_internal`] does, with the matrix and `t1 * 2^d`
+/// already expanded
+fn verify_expanded_internal<P: ParameterSet>(
+    mu: &Mu,
+    (expected_challenge_seed, z, hint): UnpackedSignature<'_, P>,
+    a: &Matrix<NTTPolynomial, P::L, P::K>,
+    shifted_t1: Vector<NTTPolynomial, P::K>,
+) -> Result<(), Error> {
+    let mut hasher = Shake256::default();
 
-    let packed_w1: [_; POLYNOMIAL_DEGREE * K / 2] =
-        w1.pack(&|slice: &[_; 2]| [(slice[0] | slice[1] << 4) as u8]);
+    let challenge = make_challenge::<P>(expected_challenge_seed).into_ntt();
+    let w1 = a * &z.into_ntt();
+    let t1 = shifted_t1 * &challenge;
+    let w1 = (w1 - t1)
+        .reduce_32()
+        .into_plain()
+        .caddq()
+        .use_hint::<P>(&hint);
 
-    let mut challenge_seed = [0u8; SEED_SIZE / 2];
+    let mut challenge_seed_buf = [0u8; CHALLENGE_SEED_SIZE_MAX];
+    let challenge_seed = &mut challenge_seed_buf[..P::CHALLENGE_SEED_SIZE];
 
-    hasher.update(&mu);
-    hasher.update(&packed_w1);
+    hasher.update(mu);
+    update_with_w1::<P>(&mut hasher, &w1);
 
     let mut reader = hasher.finalize_xof_reset();
-    reader.read(&mut challenge_seed);
+    reader.read(challenge_seed);
 
-    challenge_seed == expected_challenge_seed
+    if challenge_seed == expected_challenge_seed {
+        Ok(())
+    } else {
+        Err(Error::ChallengeMismatch)
+    }
 }
 
-fn unpack_hint(packed_hint: &[u8; POLYVECH_PACKED_SIZE]) -> Option<[[bool; POLYNOMIAL_DEGREE]; K]> {
-    let (one_indices, polynomial_indices) = packed_hint.split_at(OMEGA);
-    if !(polynomial_indices.len() == K
-        && polynomial_indices
-            .iter()
-            .all(|i| (0..OMEGA as u8).contains(i)))
-    {
-        return None;
+/// Splits a signature into the challenge seed, the response vector `z` and the hint, rejecting
+/// the vectors `z` that exceed the norm bound
+fn unpack_signature<P: ParameterSet>(signature: &[u8]) -> Result<UnpackedSignature<'_, P>, Error> {
+    let [challenge_seed, packed_z, packed_hint] =
+        signature.partition(&signature_partition_sizes::<P>());
+
+    let z = unpack_z::<P>(packed_z);
+    if z.max() >= P::GAMMA1 - P::BETA {
+        return Err(Error::NormBoundExceeded);
     }
 
-    let (polynomial_indices_it1, polynomial_indices_it2) =
-        polynomial_indices.iter().map(|&i| i as usize).tee();
-    let one_indices_in_polynomial_it = once(0)
-        .chain(polynomial_indices_it1)
-        .zip(polynomial_indices_it2)
-        .map(|(start, end)| &one_indices[start..end]);
-
-    let retval_it = one_indices_in_polynomial_it.filter_map(|slice| {
-        if is_strictly_sorted(slice.iter()) {
-            let mut poly = [false; POLYNOMIAL_DEGREE];
-            slice.iter().for_each(|&i| poly[i as usize] = true);
-            Some(poly)
-        } else {
-            None
+    Ok((challenge_seed, z, unpack_hint::<P>(packed_hint)?))
+}
+
+fn unpack_hint<P: ParameterSet>(packed_hint: &[u8]) -> Result<Hint<P>, Error> {
+    let mut retval = P::K::try_from_fn(|_| Some([false; POLYNOMIAL_DEGREE])).unwrap();
+    unpack_hint_into::<P>(packed_hint, &mut retval)?;
+
+    Ok(retval)
+}
+
+/// Unpacks a hint as [`unpack_hint`] does, directly into `hint`
+fn unpack_hint_into<P: ParameterSet>(packed_hint: &[u8], hint: &mut Hint<P>) -> Result<(), Error> {
+    let (one_indices, polynomial_indices) = packed_hint.split_at(P::OMEGA);
+    let mut start = 0;
+
+    // The accumulated numbers of ones must be non-decreasing and at most `OMEGA`, and the
+    // positions of the ones must be strictly increasing in each polynomial
+    for (&end, poly) in zip(polynomial_indices, hint.as_mut()) {
+        let slice = one_indices
+            .get(start..end as usize)
+            .ok_or(Error::InvalidHintEncoding)?;
+        start = end as usize;
+
+        if !is_strictly_sorted(slice.iter()) {
+            return Err(Error::InvalidHintEncoding);
         }
-    });
 
-    retval_it.try_collect_array()
+        *poly = [false; POLYNOMIAL_DEGREE];
+        slice.iter().for_each(|&i| poly[i as usize] = true);
+    }
+
+    // The unused positions must be zeroed, otherwise a signature would have several encodings
+    if one_indices[start..].iter().all(|&i| i == 0) {
+        Ok(())
+    } else {
+        Err(Error::InvalidHintEncoding)
+    }
 }
 
 fn is_strictly_sorted<'a>(mut it: impl Iterator<Item = &'a u8>) -> bool {
@@ -649,7 +1128,44 @@ fn is_strictly_sorted<'a>(mut it: impl Iterator<Item = &'a u8>) -> bool {
     .is_some()
 }
 
-fn z_unpacker(chunk: &[u8; 5]) -> [Coefficient; 2] {
+fn unpack_z<P: ParameterSet>(packed: &[u8]) -> Vector<PlainPolynomial, P::L> {
+    if P::GAMMA1 == 1 << 17 {
+        Pack::unpack(packed, &z_unpacker_18bits)
+    } else {
+        Pack::unpack(packed, &z_unpacker_20bits)
+    }
+}
+
+/// Unpacks `z` as [`unpack_z`] does, directly into `dst`
+fn unpack_z_into<P: ParameterSet>(packed: &[u8], dst: &mut Vector<PlainPolynomial, P::L>) {
+    for (packed_coeff, dst_coeff) in zip(packed.chunks_exact(P::POLYZ_PACKED_SIZE), dst) {
+        *dst_coeff = unpack_z_polynomial::<P>(packed_coeff);
+    }
+}
+
+/// Unpacks a single polynomial of `z`
+fn unpack_z_polynomial<P: ParameterSet>(packed: &[u8]) -> PlainPolynomial {
+    if P::GAMMA1 == 1 << 17 {
+        PlainPolynomial::unpack(packed, &z_unpacker_18bits)
+    } else {
+        PlainPolynomial::unpack(packed, &z_unpacker_20bits)
+    }
+}
+
+fn z_unpacker_18bits(chunk: &[u8; 9]) -> [Coefficient; 4] {
+    const _18BITS_MASK: Coefficient = (1 << 18) - 1;
+
+    let chunk = chunk.map(|x| x as Coefficient);
+    [
+        chunk[0] | (chunk[1] << 8) | (chunk[2] << 16),
+        (chunk[2] >> 2) | (chunk[3] << 6) | (chunk[4] << 14),
+        (chunk[4] >> 4) | (chunk[5] << 4) | (chunk[6] << 12),
+        (chunk[6] >> 6) | (chunk[7] << 2) | (chunk[8] << 10),
+    ]
+    .map(|n| (1 << 17) - (n & _18BITS_MASK))
+}
+
+fn z_unpacker_20bits(chunk: &[u8; 5]) -> [Coefficient; 2] {
     const _20BITS_MASK: Coefficient = (1 << 20) - 1;
 
     let chunk = chunk.map(|x| x as Coefficient);
@@ -657,7 +1173,7 @@ fn z_unpacker(chunk: &[u8; 5]) -> [Coefficient; 2] {
         chunk[0] | (chunk[1] << 8) | (chunk[2] << 16),
         (chunk[2] >> 4) | (chunk[3] << 4) | (chunk[4] << 12),
     ]
-    .map(|n| GAMMA1 - (n & _20BITS_MASK))
+    .map(|n| (1 << 19) - (n & _20BITS_MASK))
 }
 
 fn t1_unpacker(chunk: &[u8; 5]) -> [Coefficient; 4] {
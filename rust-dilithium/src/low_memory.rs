@@ -0,0 +1,440 @@
+//! Key generation, signing and verification within a small working memory
+//!
+//! The functions at the root of the crate hold the whole matrix `A` and the secret vectors in the
+//! NTT domain, which takes about 80 KB with Dilithium5. The functions of this module never hold
+//! more than a few polynomials besides the output: they generate the polynomials of a row of `A`
+//! and of the vector it is multiplied with each time a product needs them, unpack the polynomials
+//! of the secret vectors and of `z` from the packed key or signature, and pack each polynomial of
+//! the result as soon as it is computed. They yield the same keys, signatures and verification
+//! results as the functions at the root of the crate, within 32 KB of stack with every parameter
+//! set, which the stack tests check.
+//!
+//! The price is speed. The masking vector and the rows of `A` are generated again for each row of
+//! `w`, and `w` is computed twice per signing attempt, once for its high bits and once more after
+//! the challenge for its low bits.
+
+use core::{array, iter::zip};
+
+use sha3::{
+    digest::{ExtendableOutput, ExtendableOutputReset, Update, XofReader},
+    Shake256,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    counter::Counter,
+    expand::{expand_a_polynomial, expand_s_polynomial, expand_y_polynomial},
+    hash_message, make_challenge, make_hint_into, make_mu, make_rho_prime, make_tr, pack_eta,
+    pack_hint_polynomial, pack_z,
+    packing::Pack,
+    params::{Dim, Dimension},
+    polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial, NB_COEFFICIENTS},
+    public_key_partition_sizes, secret_key_partition_sizes, signature_partition_sizes, t0_packer,
+    t0_unpacker, t1_packer, t1_unpacker, unpack_eta, unpack_hint, unpack_z_polynomial,
+    update_with_w1_polynomial,
+    vector::Vector,
+    Error, Mu, ParameterSet, Partition, PartitionMut, PublicKey, SecretKey, Seed, Signature,
+    CHALLENGE_SEED_SIZE_MAX, POLYW1_PACKED_SIZE_MAX, RANDOMNESS_SIZE, SEED_SIZE, T0_PACKED_SIZE,
+    T1_PACKED_SIZE,
+};
+
+/// Single polynomial, wrapped in a vector of dimension 1 to reuse the vector arithmetic
+type Single<T> = Vector<T, Dim<1>>;
+
+/// Derives the same key pair as [`crate::make_keys`]
+pub fn make_keys<P: ParameterSet, Ctr: Counter>(
+    seed: &Seed,
+) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
+    let mut hasher = Shake256::default();
+    hasher.update(seed);
+
+    Ok(make_keys_internal::<P, Ctr>(hasher))
+}
+
+/// Signs `msg` in the deterministic variant, see [`crate::sign`]
+pub fn sign<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), &[], sk))
+}
+
+/// Signs `msg` in the hedged variant, where `rnd` must be fresh randomness, see
+/// [`crate::sign_hedged`]
+pub fn sign_hedged<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), rnd, sk))
+}
+
+/// Signs a message given by its representative `mu`, see [`crate::sign_mu`]
+pub fn sign_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, &[], sk))
+}
+
+/// Signs a message given by its representative `mu` in the hedged variant, see
+/// [`crate::sign_mu_hedged`]
+pub fn sign_mu_hedged<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, rnd, sk))
+}
+
+/// Verifies `signature` against `msg`, see [`crate::verify`]
+pub fn verify<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(&make_mu::<P>(msg, pk), signature, pk)
+}
+
+/// Verifies `signature` against a message given by its representative `mu`, see
+/// [`crate::verify_mu`]
+pub fn verify_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(mu, signature, pk)
+}
+
+/// Derives a key pair from `hasher`, which must have absorbed the seed of the key pair
+pub(crate) fn make_keys_internal<P: ParameterSet, Ctr: Counter>(
+    hasher: Shake256,
+) -> (PublicKey<P>, SecretKey<P>) {
+    let mut retval = (PublicKey::<P>::zeroed(), SecretKey::<P>::zeroed());
+    make_keys_into::<P, Ctr>(hasher, &mut retval.0, &mut retval.1);
+
+    retval
+}
+
+/// Writes the key pair derived from `hasher` to `pk` and `sk`, which must be zeroed. `s1` is
+/// packed into the secret key first and unpacked again one polynomial at a time for each row of
+/// `t`, which is packed into the keys as soon as it is computed.
+#[inline(never)]
+fn make_keys_into<P: ParameterSet, Ctr: Counter>(
+    mut hasher: Shake256,
+    pk: &mut PublicKey<P>,
+    sk: &mut SecretKey<P>,
+) {
+    let mut rho = [0u8; SEED_SIZE / 2];
+    let mut rho_prime = Zeroizing::new([0u8; SEED_SIZE]);
+    let mut key = Zeroizing::new([0u8; SEED_SIZE / 2]);
+
+    let mut reader = hasher.finalize_xof_reset();
+    reader.read(&mut rho);
+    reader.read(rho_prime.as_mut());
+    reader.read(key.as_mut());
+
+    let [pk_rho, packed_t1] = pk
+        .as_bytes_mut()
+        .partition_mut(&public_key_partition_sizes::<P>());
+    let [sk_rho, sk_key, _, packed_s1, packed_s2, packed_t0] = sk
+        .as_bytes_mut()
+        .partition_mut(&secret_key_partition_sizes::<P>());
+
+    pk_rho.copy_from_slice(&rho);
+    sk_rho.copy_from_slice(&rho);
+    sk_key.copy_from_slice(key.as_ref());
+
+    let mut s_ctr = Ctr::new_wide(&rho_prime);
+    for (nonce, packed_s1_j) in zip(0.., packed_s1.chunks_exact_mut(P::ETA_PACKED_SIZE)) {
+        pack_eta::<P>(&expand_s_polynomial::<P>(&mut s_ctr, nonce), packed_s1_j);
+    }
+
+    let packed_s1 = &*packed_s1;
+    let s1_j = |j: usize| {
+        let packed_s1_j = &packed_s1[j * P::ETA_PACKED_SIZE..][..P::ETA_PACKED_SIZE];
+        unpack_eta::<P, PlainPolynomial>(packed_s1_j).into_ntt()
+    };
+
+    let mut a_ctr = Ctr::new(&rho);
+    let mut product = Single::<NTTPolynomial>::default();
+    // `t` then `t0`
+    let mut t = Single::<PlainPolynomial>::default();
+    // `s2` then `t1`
+    let mut t1 = Single::<PlainPolynomial>::default();
+
+    let rows_it = zip(
+        packed_s2.chunks_exact_mut(P::ETA_PACKED_SIZE),
+        zip(
+            packed_t0.chunks_exact_mut(T0_PACKED_SIZE),
+            packed_t1.chunks_exact_mut(T1_PACKED_SIZE),
+        ),
+    );
+
+    for (i, (packed_s2_i, (packed_t0_i, packed_t1_i))) in rows_it.enumerate() {
+        *t1.as_polynomial_mut() = expand_s_polynomial::<P>(&mut s_ctr, (P::L::N + i) as u16);
+        pack_eta::<P>(&t1, packed_s2_i);
+
+        make_w_row_into::<P>(&mut a_ctr, i, s1_j, &mut product, &mut t);
+        t += &t1;
+        t.power2round_into(&mut t1);
+
+        t.pack_inplace(&t0_packer, packed_t0_i);
+        t1.pack_inplace(&t1_packer, packed_t1_i);
+    }
+
+    let tr = make_tr::<P>(pk);
+    let [_, _, sk_tr, ..] = sk
+        .as_bytes_mut()
+        .partition_mut(&secret_key_partition_sizes::<P>());
+    sk_tr.copy_from_slice(&tr[..P::TR_SIZE]);
+}
+
+/// Signs the message representative `mu` as [`crate::sign_internal`] does, with the secret
+/// vectors unpacked from `sk` when needed
+fn sign_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    rnd: &[u8],
+    sk: &SecretKey<P>,
+) -> Signature<P> {
+    let [_, key, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let rho_prime = make_rho_prime(key, rnd, mu);
+
+    let mut retval = Signature::<P>::zeroed();
+
+    // Will not panic since input iterator is infinite
+    let sample = |&nonce: &u16| sample_signature::<P, Ctr>(nonce, &rho_prime, mu, sk, &mut retval);
+    (0..).find(sample).unwrap();
+
+    retval
+}
+
+/// Makes a signing attempt as [`crate::sample_signature`] does. The rows of `w` are computed one at
+/// a time to absorb their high bits, with the polynomials of the masking vector generated again
+/// for each of them. Once the challenge is known, `z` is computed and packed one polynomial at a
+/// time, and each row of `w` is computed again to get the low bits that the hint needs.
+///
+/// The attempt is written to `signature`, and whether it succeeded is returned.
+#[inline(never)]
+fn sample_signature<P: ParameterSet, Ctr: Counter>(
+    nonce: u16,
+    rho_prime: &[u8; SEED_SIZE],
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    signature: &mut Signature<P>,
+) -> bool {
+    let [rho, _, _, packed_s1, packed_s2, packed_t0] =
+        sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let mut hasher = Shake256::default();
+    // Unwrapping is safe here because the slice is of the right size
+    let mut a_ctr = Ctr::new(rho.try_into().unwrap());
+    let mut y_ctr = Ctr::new_wide(rho_prime);
+    let y_nonce = |j: usize| P::L::N as u16 * nonce + j as u16;
+    let mut packed_w1_buf = [0u8; POLYW1_PACKED_SIZE_MAX];
+    let packed_w1 = &mut packed_w1_buf[..P::POLYW1_PACKED_SIZE];
+
+    let mut product = Single::<NTTPolynomial>::default();
+    let mut w0 = Single::<PlainPolynomial>::default();
+    let mut w1 = Single::<PlainPolynomial>::default();
+    // `z` then `cs2` and `ct`
+    let mut plain = Single::<PlainPolynomial>::default();
+
+    hasher.update(mu);
+
+    for i in 0..P::K::N {
+        make_w_row_of_y_into::<P>(&mut a_ctr, &mut y_ctr, y_nonce, i, &mut product, &mut w0);
+        w0.decompose_into::<P>(&mut w1);
+        update_with_w1_polynomial::<P>(&mut hasher, w1.as_polynomial(), packed_w1);
+    }
+
+    let [challenge_seed, packed_z, packed_hint] = signature
+        .as_bytes_mut()
+        .partition_mut(&signature_partition_sizes::<P>());
+
+    let mut reader = hasher.finalize_xof_reset();
+    reader.read(challenge_seed);
+
+    let challenge = make_challenge::<P>(challenge_seed).into_ntt();
+
+    let z_rows_it = zip(
+        packed_s1.chunks_exact(P::ETA_PACKED_SIZE),
+        packed_z.chunks_exact_mut(P::POLYZ_PACKED_SIZE),
+    );
+
+    for (j, (packed_s1_j, packed_z_j)) in z_rows_it.enumerate() {
+        *product.as_polynomial_mut() = unpack_eta::<P, PlainPolynomial>(packed_s1_j).into_ntt();
+        product *= &challenge;
+        product.plain_into(&mut plain);
+        *plain.as_polynomial_mut() += &expand_y_polynomial::<P>(&mut y_ctr, y_nonce(j));
+        plain.reduce_32_inplace();
+        if plain.max() >= P::GAMMA1 - P::BETA {
+            return false;
+        }
+
+        pack_z::<P>(&plain, packed_z_j);
+    }
+
+    let mut hint_i = [[false; NB_COEFFICIENTS]];
+    let mut hint_bits_count = 0;
+
+    // The hint is packed one row at a time, over the positions left by the previous attempts
+    packed_hint.fill(0);
+
+    let hint_rows_it = zip(
+        packed_s2.chunks_exact(P::ETA_PACKED_SIZE),
+        packed_t0.chunks_exact(T0_PACKED_SIZE),
+    );
+
+    for (i, (packed_s2_i, packed_t0_i)) in hint_rows_it.enumerate() {
+        make_w_row_of_y_into::<P>(&mut a_ctr, &mut y_ctr, y_nonce, i, &mut product, &mut w0);
+        w0.decompose_into::<P>(&mut w1);
+
+        // `w0` is replaced by `w0 - cs2`, then by `w0 - cs2 + ct` from which the hint is made
+        *product.as_polynomial_mut() = unpack_eta::<P, PlainPolynomial>(packed_s2_i).into_ntt();
+        product *= &challenge;
+        product.plain_into(&mut plain);
+        w0 -= &plain;
+        w0.reduce_32_inplace();
+        if w0.max() >= P::GAMMA2 - P::BETA {
+            return false;
+        }
+
+        *product.as_polynomial_mut() =
+            PlainPolynomial::unpack(packed_t0_i, &t0_unpacker).into_ntt();
+        product *= &challenge;
+        product.plain_into(&mut plain);
+        plain.reduce_32_inplace();
+        if plain.max() >= P::GAMMA2 {
+            return false;
+        }
+
+        w0 += &plain;
+        if hint_bits_count + make_hint_into::<P, Dim<1>>(&w0, &w1, &mut hint_i) > P::OMEGA {
+            return false;
+        }
+
+        let [hint_i] = &hint_i;
+        hint_bits_count = pack_hint_polynomial::<P>(hint_i, i, hint_bits_count, packed_hint);
+    }
+
+    true
+}
+
+/// Verifies `signature` against the message representative `mu` as [`crate::verify_internal`]
+/// does, one row of `w1` at a time, with the polynomials of `z` unpacked again for each row
+fn verify_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    let [rho, packed_t1] = pk.as_bytes().partition(&public_key_partition_sizes::<P>());
+    let [expected_challenge_seed, packed_z, packed_hint] = signature
+        .as_bytes()
+        .partition(&signature_partition_sizes::<P>());
+
+    for packed_z_j in packed_z.chunks_exact(P::POLYZ_PACKED_SIZE) {
+        if unpack_z_polynomial::<P>(packed_z_j).max() >= P::GAMMA1 - P::BETA {
+            return Err(Error::NormBoundExceeded);
+        }
+    }
+
+    let hint = unpack_hint::<P>(packed_hint)?;
+    let challenge = make_challenge::<P>(expected_challenge_seed).into_ntt();
+    let z_j = |j: usize| {
+        let packed_z_j = &packed_z[j * P::POLYZ_PACKED_SIZE..][..P::POLYZ_PACKED_SIZE];
+        unpack_z_polynomial::<P>(packed_z_j).into_ntt()
+    };
+
+    let mut hasher = Shake256::default();
+    let mut packed_w1_buf = [0u8; POLYW1_PACKED_SIZE_MAX];
+    let packed_w1 = &mut packed_w1_buf[..P::POLYW1_PACKED_SIZE];
+
+    let mut product = Single::<NTTPolynomial>::default();
+    let mut shifted_ct1 = Single::<NTTPolynomial>::default();
+    // `t1 * 2^d` then `w1`
+    let mut plain = Single::<PlainPolynomial>::default();
+
+    hasher.update(mu);
+
+    // Unwrapping is safe here because the slice is of the right size
+    let mut a_ctr = Ctr::new(rho.try_into().unwrap());
+    let rows_it = zip(packed_t1.chunks_exact(T1_PACKED_SIZE), hint.as_ref());
+
+    for (i, (packed_t1_i, hint_i)) in rows_it.enumerate() {
+        make_row_product_into::<P>(&mut a_ctr, i, z_j, &mut product);
+
+        *plain.as_polynomial_mut() = PlainPolynomial::unpack(packed_t1_i, &t1_unpacker);
+        plain.shift_d_inplace();
+        plain.ntt_into(&mut shifted_ct1);
+        shifted_ct1 *= &challenge;
+
+        product -= &shifted_ct1;
+        product.reduce_32_inplace();
+        product.plain_into(&mut plain);
+        plain.caddq_inplace();
+        plain.use_hint_inplace::<P>(array::from_ref(hint_i));
+
+        update_with_w1_polynomial::<P>(&mut hasher, plain.as_polynomial(), packed_w1);
+    }
+
+    let mut challenge_seed_buf = [0u8; CHALLENGE_SEED_SIZE_MAX];
+    let challenge_seed = &mut challenge_seed_buf[..P::CHALLENGE_SEED_SIZE];
+
+    let mut reader = hasher.finalize_xof();
+    reader.read(challenge_seed);
+
+    if challenge_seed == expected_challenge_seed {
+        Ok(())
+    } else {
+        Err(Error::ChallengeMismatch)
+    }
+}
+
+/// Writes the row `i` of `w = A * y` to `dst` as [`make_w_row_into`] does, generating the
+/// polynomials of the masking vector again
+fn make_w_row_of_y_into<P: ParameterSet>(
+    a_ctr: &mut impl Counter,
+    y_ctr: &mut impl Counter,
+    y_nonce: impl Fn(usize) -> u16,
+    i: usize,
+    product: &mut Single<NTTPolynomial>,
+    dst: &mut Single<PlainPolynomial>,
+) {
+    let y_j = |j| expand_y_polynomial::<P>(y_ctr, y_nonce(j)).into_ntt();
+    make_w_row_into::<P>(a_ctr, i, y_j, product, dst);
+}
+
+/// Writes the row `i` of `w` to `dst` as [`crate::make_w`] does, where `v` gives the polynomials
+/// of the vector in the NTT domain and `product` holds the row product
+fn make_w_row_into<P: ParameterSet>(
+    a_ctr: &mut impl Counter,
+    i: usize,
+    v: impl FnMut(usize) -> NTTPolynomial,
+    product: &mut Single<NTTPolynomial>,
+    dst: &mut Single<PlainPolynomial>,
+) {
+    make_row_product_into::<P>(a_ctr, i, v, product);
+    product.reduce_32_inplace();
+    product.plain_into(dst);
+    dst.caddq_inplace();
+}
+
+/// Writes the product of the row `i` of `A` and the vector whose polynomials `v` gives to `dst`,
+/// generating the polynomials of the row and of the vector one at a time
+fn make_row_product_into<P: ParameterSet>(
+    a_ctr: &mut impl Counter,
+    i: usize,
+    mut v: impl FnMut(usize) -> NTTPolynomial,
+    dst: &mut Single<NTTPolynomial>,
+) {
+    let dst = dst.as_polynomial_mut();
+    dst.zeroize();
+
+    for j in 0..P::L::N {
+        dst.add_product_assign(&expand_a_polynomial(a_ctr, i, j), &v(j));
+    }
+}
@@ -0,0 +1,648 @@
+//! ML-DSA as standardized in FIPS 204
+//!
+//! Compared to the round-3 Dilithium implemented at the root of the crate, the key pair seed is
+//! bound to the dimensions of the parameter set, the hash of the public key is 64 bytes long, the
+//! size of the challenge seed depends on the parameter set, and messages are signed along with a
+//! context string. The expansion of the matrix and of the vectors always relies on SHAKE. The
+//! parameter sets to use with this module are [`MlDsa44`], [`MlDsa65`] and [`MlDsa87`].
+//!
+//! The HashML-DSA variant signs the digest of a message instead of the message itself, so that
+//! large messages can be hashed once away from the signer.
+//!
+//! [`SigningKey`] and [`VerifyingKey`] implement the traits of the RustCrypto `signature` crate,
+//! with an empty context string.
+//!
+//! [`MlDsa44`]: crate::params::MlDsa44
+//! [`MlDsa65`]: crate::params::MlDsa65
+//! [`MlDsa87`]: crate::params::MlDsa87
+
+use core::{
+    fmt::{self, Debug, Formatter},
+    mem::size_of,
+};
+
+use rand_core::CryptoRngCore;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+use signature::{Keypair, RandomizedSigner, Signer, Verifier};
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    counter::ShakeCounter,
+    expanded::{ExpandedSigningKey, ExpandedVerifyingKey},
+    low_memory, make_keys_internal, make_tr,
+    params::Dimension,
+    secret_key_partition_sizes, sign_internal, verify_internal,
+    workspace::{self, Workspace},
+    CompactSecretKey, Error, KeyPair, Mu, ParameterSet, Partition, PublicKey, SecretKey, Seed,
+    Signature, RANDOMNESS_SIZE,
+};
+
+pub const CONTEXT_SIZE_MAX: usize = 255;
+
+const PURE_DOMAIN_SEPARATOR: u8 = 0;
+const PRE_HASH_DOMAIN_SEPARATOR: u8 = 1;
+
+/// Digest of a message signed with HashML-DSA, tagged with the hash function that computed it
+#[derive(Clone, Copy, Debug)]
+pub enum PreHash<'a> {
+    Sha256(&'a [u8; 32]),
+    Sha512(&'a [u8; 64]),
+    Shake128(&'a [u8; 32]),
+}
+
+impl PreHash<'_> {
+    /// DER encodings of the object identifiers of the supported hash functions
+    pub const SHA256_OID: [u8; 11] = [
+        0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+    ];
+    pub const SHA512_OID: [u8; 11] = [
+        0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03,
+    ];
+    pub const SHAKE128_OID: [u8; 11] = [
+        0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0b,
+    ];
+
+    pub fn oid(&self) -> &'static [u8; 11] {
+        match self {
+            Self::Sha256(_) => &Self::SHA256_OID,
+            Self::Sha512(_) => &Self::SHA512_OID,
+            Self::Shake128(_) => &Self::SHAKE128_OID,
+        }
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        match self {
+            Self::Sha256(digest) => *digest,
+            Self::Sha512(digest) => *digest,
+            Self::Shake128(digest) => *digest,
+        }
+    }
+}
+
+pub fn make_keys<P: ParameterSet>(seed: &Seed) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
+    Ok(make_keys_internal::<P, ShakeCounter>(seed_hasher::<P>(
+        seed,
+    )))
+}
+
+/// Derives the same key pair as [`make_keys`], but with the secret key stored as `seed` only
+pub fn make_compact_keys<P: ParameterSet>(
+    seed: &Seed,
+) -> Result<(PublicKey<P>, CompactSecretKey<P>), Error> {
+    let (pk, _) = make_keys::<P>(seed)?;
+
+    Ok((pk, CompactSecretKey::new(seed)))
+}
+
+/// Expands `sk` into the full secret key made by [`make_keys`], which all the signing functions
+/// take
+pub fn expand_secret_key<P: ParameterSet>(sk: &CompactSecretKey<P>) -> SecretKey<P> {
+    make_keys_internal::<P, ShakeCounter>(seed_hasher::<P>(sk.seed())).1
+}
+
+/// Absorbs the key pair seed, bound to the dimensions of the parameter set
+fn seed_hasher<P: ParameterSet>(seed: &Seed) -> Shake256 {
+    let mut retval = Shake256::default();
+    retval.update(seed);
+    retval.update(&[P::K::N as u8, P::L::N as u8]);
+
+    retval
+}
+
+/// Rebuilds the public key of `sk`, see [`crate::public_key_from_secret_key`]
+pub fn public_key_from_secret_key<P: ParameterSet>(
+    sk: &SecretKey<P>,
+) -> Result<PublicKey<P>, Error> {
+    crate::public_key_from_secret_key::<P, ShakeCounter>(sk)
+}
+
+/// Checks that `sk` is consistent, see [`crate::validate_secret_key`]
+pub fn validate_secret_key<P: ParameterSet>(sk: &SecretKey<P>) -> Result<(), Error> {
+    crate::validate_secret_key::<P, ShakeCounter>(sk)
+}
+
+/// Checks that `sk` is consistent and that `pk` is its public key, see [`crate::validate_keypair`]
+pub fn validate_keypair<P: ParameterSet>(
+    pk: &PublicKey<P>,
+    sk: &SecretKey<P>,
+) -> Result<(), Error> {
+    crate::validate_keypair::<P, ShakeCounter>(pk, sk)
+}
+
+/// Signs `msg` in the hedged variant, where `rnd` must be fresh randomness
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn sign<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    sign_parts::<P>(&[msg], ctx, sk, rnd)
+}
+
+/// Signs the concatenation of `parts` as [`sign`] signs a message, without copying them
+pub(crate) fn sign_parts<P: ParameterSet>(
+    parts: &[&[u8]],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let mu = hash_message(tr, PURE_DOMAIN_SEPARATOR, ctx, parts)?;
+
+    Ok(sign_internal::<P, ShakeCounter>(&mu, rnd, sk))
+}
+
+/// Signs `msg` in the hedged variant with randomness drawn from `rng`
+pub fn sign_with_rng<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Signature<P>, Error> {
+    let mut rnd = Zeroizing::new([0u8; RANDOMNESS_SIZE]);
+    rng.fill_bytes(rnd.as_mut());
+
+    sign::<P>(msg, ctx, sk, &rnd)
+}
+
+/// Signs `msg` in the deterministic variant, which is reproducible but more exposed to fault and
+/// side-channel attacks than [`sign`]
+pub fn sign_deterministic<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    sign::<P>(msg, ctx, sk, &[0; RANDOMNESS_SIZE])
+}
+
+/// Signs `msg` with a compact secret key in the hedged variant, which yields the same signature as
+/// [`sign`]
+///
+/// The full secret key is expanded from the seed for the duration of the call, and wiped
+/// afterwards.
+pub fn sign_compact<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &CompactSecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    sign::<P>(msg, ctx, &expand_secret_key::<P>(sk), rnd)
+}
+
+/// Signs `msg` with a compact secret key in the deterministic variant, which yields the same
+/// signature as [`sign_deterministic`]
+pub fn sign_compact_deterministic<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &CompactSecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    sign_compact::<P>(msg, ctx, sk, &[0; RANDOMNESS_SIZE])
+}
+
+pub fn verify<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_mu::<P>(&make_mu::<P>(msg, ctx, pk)?, signature, pk)
+}
+
+/// Verifies `signature` against the concatenation of `parts` as [`verify`] does against a
+/// message, without copying them
+#[cfg(any(feature = "cose", feature = "jose"))]
+pub(crate) fn verify_parts<P: ParameterSet>(
+    parts: &[&[u8]],
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    let tr = make_tr::<P>(pk);
+    let mu = hash_message(&tr[..P::TR_SIZE], PURE_DOMAIN_SEPARATOR, ctx, parts)?;
+
+    verify_mu::<P>(&mu, signature, pk)
+}
+
+/// Signs `msg` with an expanded secret key in the hedged variant, which yields the same signature
+/// as [`sign`]
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn sign_expanded<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &ExpandedSigningKey<P, ShakeCounter>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let mu = hash_message(sk.tr(), PURE_DOMAIN_SEPARATOR, ctx, &[msg])?;
+
+    Ok(sk.sign_internal(&mu, rnd))
+}
+
+/// Signs `msg` with an expanded secret key in the deterministic variant, which yields the same
+/// signature as [`sign_deterministic`]
+pub fn sign_expanded_deterministic<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &ExpandedSigningKey<P, ShakeCounter>,
+) -> Result<Signature<P>, Error> {
+    sign_expanded::<P>(msg, ctx, sk, &[0; RANDOMNESS_SIZE])
+}
+
+/// Verifies `signature` against `msg` with an expanded public key, as [`verify`] does
+pub fn verify_expanded<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &ExpandedVerifyingKey<P, ShakeCounter>,
+) -> Result<(), Error> {
+    let mu = hash_message(pk.tr(), PURE_DOMAIN_SEPARATOR, ctx, &[msg])?;
+
+    pk.verify_mu(&mu, signature)
+}
+
+/// Verifies each signature of `items` against its message and `ctx`, expanding the public key
+/// only once
+///
+/// The results are yielded in the order of `items`, as [`verify`] would return them.
+pub fn verify_batch<'a, P: ParameterSet>(
+    items: &'a [(&'a [u8], Signature<P>)],
+    ctx: &'a [u8],
+    pk: &PublicKey<P>,
+) -> impl Iterator<Item = Result<(), Error>> + 'a {
+    let expanded_pk = ExpandedVerifyingKey::<P, ShakeCounter>::new(pk);
+
+    items
+        .iter()
+        .map(move |(msg, signature)| verify_expanded::<P>(msg, ctx, signature, &expanded_pk))
+}
+
+/// Derives the same key pair as [`make_keys`] within a small working memory, see
+/// [`crate::low_memory`]
+// Inlined so that the key pair is built in the result of the caller instead of being copied into
+// it, which would take as much stack again as the keys
+#[inline]
+pub fn make_keys_low_memory<P: ParameterSet>(
+    seed: &Seed,
+) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
+    Ok(low_memory::make_keys_internal::<P, ShakeCounter>(
+        seed_hasher::<P>(seed),
+    ))
+}
+
+/// Signs `msg` in the hedged variant within a small working memory, which yields the same
+/// signature as [`sign`]
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn sign_low_memory<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let mu = hash_message(tr, PURE_DOMAIN_SEPARATOR, ctx, &[msg])?;
+
+    low_memory::sign_mu_hedged::<P, ShakeCounter>(&mu, sk, rnd)
+}
+
+/// Signs `msg` in the deterministic variant within a small working memory, which yields the same
+/// signature as [`sign_deterministic`]
+pub fn sign_low_memory_deterministic<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    sign_low_memory::<P>(msg, ctx, sk, &[0; RANDOMNESS_SIZE])
+}
+
+/// Verifies `signature` against `msg` within a small working memory, as [`verify`] does
+pub fn verify_low_memory<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    low_memory::verify_mu::<P, ShakeCounter>(&make_mu::<P>(msg, ctx, pk)?, signature, pk)
+}
+
+/// Derives the same key pair as [`make_keys`] in the workspace `ws`, see [`crate::workspace`]
+pub fn make_keys_with_workspace<P: ParameterSet>(
+    seed: &Seed,
+    ws: &mut Workspace<P>,
+) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
+    Ok(workspace::make_keys_internal::<P, ShakeCounter>(
+        seed_hasher::<P>(seed),
+        ws,
+    ))
+}
+
+/// Signs `msg` in the hedged variant in the workspace `ws`, which yields the same signature as
+/// [`sign`]
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn sign_with_workspace<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let mu = hash_message(tr, PURE_DOMAIN_SEPARATOR, ctx, &[msg])?;
+
+    workspace::sign_mu_hedged::<P, ShakeCounter>(&mu, sk, rnd, ws)
+}
+
+/// Signs `msg` in the deterministic variant in the workspace `ws`, which yields the same
+/// signature as [`sign_deterministic`]
+pub fn sign_with_workspace_deterministic<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    sign_with_workspace::<P>(msg, ctx, sk, &[0; RANDOMNESS_SIZE], ws)
+}
+
+/// Verifies `signature` against `msg` in the workspace `ws`, as [`verify`] does
+pub fn verify_with_workspace<P: ParameterSet>(
+    msg: &[u8],
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<(), Error> {
+    workspace::verify_mu::<P, ShakeCounter>(&make_mu::<P>(msg, ctx, pk)?, signature, pk, ws)
+}
+
+/// Computes the message representative `mu` that [`sign_mu`] and [`verify_mu`] take in place of
+/// the message and its context string
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn make_mu<P: ParameterSet>(msg: &[u8], ctx: &[u8], pk: &PublicKey<P>) -> Result<Mu, Error> {
+    let tr = make_tr::<P>(pk);
+
+    hash_message(&tr[..P::TR_SIZE], PURE_DOMAIN_SEPARATOR, ctx, &[msg])
+}
+
+/// Signs a message given by its representative `mu` in the hedged variant, where `rnd` must be
+/// fresh randomness
+pub fn sign_mu<P: ParameterSet>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, ShakeCounter>(mu, rnd, sk))
+}
+
+/// Verifies `signature` against a message given by its representative `mu`
+pub fn verify_mu<P: ParameterSet>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, ShakeCounter>(mu, signature, pk)
+}
+
+/// Signs the digest of a message with HashML-DSA in the hedged variant, where `rnd` must be fresh
+/// randomness
+///
+/// Fails with [`Error::ContextTooLong`] if `ctx` is longer than `CONTEXT_SIZE_MAX`.
+pub fn sign_pre_hashed<P: ParameterSet>(
+    digest: PreHash,
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+    let mu = hash_message(
+        tr,
+        PRE_HASH_DOMAIN_SEPARATOR,
+        ctx,
+        &[digest.oid(), digest.digest()],
+    )?;
+
+    Ok(sign_internal::<P, ShakeCounter>(&mu, rnd, sk))
+}
+
+/// Signs the digest of a message with HashML-DSA in the hedged variant with randomness drawn from
+/// `rng`
+pub fn sign_pre_hashed_with_rng<P: ParameterSet>(
+    digest: PreHash,
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Signature<P>, Error> {
+    let mut rnd = Zeroizing::new([0u8; RANDOMNESS_SIZE]);
+    rng.fill_bytes(rnd.as_mut());
+
+    sign_pre_hashed::<P>(digest, ctx, sk, &rnd)
+}
+
+/// Signs the digest of a message with HashML-DSA in the deterministic variant
+pub fn sign_pre_hashed_deterministic<P: ParameterSet>(
+    digest: PreHash,
+    ctx: &[u8],
+    sk: &SecretKey<P>,
+) -> Result<Signature<P>, Error> {
+    sign_pre_hashed::<P>(digest, ctx, sk, &[0; RANDOMNESS_SIZE])
+}
+
+pub fn verify_pre_hashed<P: ParameterSet>(
+    digest: PreHash,
+    ctx: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+) -> Result<(), Error> {
+    let tr = make_tr::<P>(pk);
+    let mu = hash_message(
+        &tr[..P::TR_SIZE],
+        PRE_HASH_DOMAIN_SEPARATOR,
+        ctx,
+        &[digest.oid(), digest.digest()],
+    )?;
+
+    verify_internal::<P, ShakeCounter>(&mu, signature, pk)
+}
+
+/// Key pair which signs through the `signature` traits with an empty context string, as
+/// [`crate::signing::SigningKey`] does for round-3 Dilithium
+pub struct SigningKey<P: ParameterSet> {
+    key_pair: KeyPair<P>,
+    /// Seed of the key pair, when it was derived from one
+    compact_secret_key: Option<CompactSecretKey<P>>,
+}
+
+/// Public key which verifies through the `signature` traits with an empty context string
+pub struct VerifyingKey<P: ParameterSet> {
+    public_key: PublicKey<P>,
+}
+
+impl<P: ParameterSet> SigningKey<P> {
+    pub fn new(key_pair: KeyPair<P>) -> Self {
+        Self {
+            key_pair,
+            compact_secret_key: None,
+        }
+    }
+
+    /// Derives the key pair of `seed` as [`make_keys`] does, and keeps the seed
+    pub fn from_seed(seed: &Seed) -> Result<Self, Error> {
+        let mut retval = make_keys::<P>(seed).map(|key_pair| Self::new(key_pair.into()))?;
+        retval.compact_secret_key = Some(CompactSecretKey::new(seed));
+
+        Ok(retval)
+    }
+
+    /// Expands `sk` and keeps it
+    pub fn from_compact_secret_key(sk: &CompactSecretKey<P>) -> Result<Self, Error> {
+        Self::from_seed(sk.seed())
+    }
+
+    pub fn key_pair(&self) -> &KeyPair<P> {
+        &self.key_pair
+    }
+
+    pub fn public_key(&self) -> &PublicKey<P> {
+        self.key_pair.public_key()
+    }
+
+    pub fn secret_key(&self) -> &SecretKey<P> {
+        self.key_pair.secret_key()
+    }
+
+    /// Returns the seed of the key pair, unless the key was made without it
+    pub fn compact_secret_key(&self) -> Option<&CompactSecretKey<P>> {
+        self.compact_secret_key.as_ref()
+    }
+
+    pub fn into_key_pair(self) -> KeyPair<P> {
+        self.key_pair
+    }
+}
+
+impl<P: ParameterSet> VerifyingKey<P> {
+    pub fn new(public_key: PublicKey<P>) -> Self {
+        Self { public_key }
+    }
+
+    pub fn public_key(&self) -> &PublicKey<P> {
+        &self.public_key
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.public_key.as_bytes()
+    }
+}
+
+impl<P: ParameterSet> Signer<Signature<P>> for SigningKey<P> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+        Ok(sign_deterministic::<P>(msg, &[], self.secret_key())?)
+    }
+}
+
+impl<P: ParameterSet> RandomizedSigner<Signature<P>> for SigningKey<P> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<P>, signature::Error> {
+        Ok(sign_with_rng::<P>(msg, &[], self.secret_key(), rng)?)
+    }
+}
+
+impl<P: ParameterSet> Keypair for SigningKey<P> {
+    type VerifyingKey = VerifyingKey<P>;
+
+    fn verifying_key(&self) -> VerifyingKey<P> {
+        VerifyingKey::new(*self.public_key())
+    }
+}
+
+impl<P: ParameterSet> Verifier<Signature<P>> for VerifyingKey<P> {
+    fn verify(&self, msg: &[u8], signature: &Signature<P>) -> Result<(), signature::Error> {
+        Ok(verify::<P>(msg, &[], signature, &self.public_key)?)
+    }
+}
+
+impl<P: ParameterSet> Clone for SigningKey<P> {
+    fn clone(&self) -> Self {
+        Self {
+            key_pair: self.key_pair.clone(),
+            compact_secret_key: self.compact_secret_key.clone(),
+        }
+    }
+}
+
+impl<P: ParameterSet> Debug for SigningKey<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("key_pair", &self.key_pair)
+            .finish()
+    }
+}
+
+impl<P: ParameterSet> ZeroizeOnDrop for SigningKey<P> {}
+
+impl<P: ParameterSet> Clone for VerifyingKey<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: ParameterSet> Copy for VerifyingKey<P> {}
+
+impl<P: ParameterSet> PartialEq for VerifyingKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.public_key == other.public_key
+    }
+}
+
+impl<P: ParameterSet> Eq for VerifyingKey<P> {}
+
+impl<P: ParameterSet> Debug for VerifyingKey<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyingKey")
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl<P: ParameterSet> AsRef<[u8]> for VerifyingKey<P> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<P: ParameterSet> From<PublicKey<P>> for VerifyingKey<P> {
+    fn from(public_key: PublicKey<P>) -> Self {
+        Self::new(public_key)
+    }
+}
+
+/// Hashes the message, given as a list of chunks, along with the domain separator of the variant
+/// and the context string
+fn hash_message(tr: &[u8], domain_separator: u8, ctx: &[u8], msg: &[&[u8]]) -> Result<Mu, Error> {
+    let ctx_size = u8::try_from(ctx.len()).map_err(|_| Error::ContextTooLong)?;
+
+    let mut hasher = Shake256::default();
+    let mut retval = [0u8; size_of::<Mu>()];
+
+    hasher.update(tr);
+    hasher.update(&[domain_separator, ctx_size]);
+    hasher.update(ctx);
+
+    for chunk in msg {
+        hasher.update(chunk);
+    }
+
+    let mut reader = hasher.finalize_xof();
+    reader.read(&mut retval);
+
+    Ok(retval)
+}
@@ -0,0 +1,220 @@
+use crate::{
+    coefficient::Coefficient, try_from_fn, Error, Q, SEED_SIZE, T0_PACKED_SIZE, T1_PACKED_SIZE,
+};
+use core::fmt::Debug;
+
+/// Size of a vector or of a matrix side, known at compile time
+///
+/// Only implemented by [`Dim`], whose arrays of polynomials are valid when zeroed, which lets a
+/// [`Workspace`](crate::workspace::Workspace) be initialized without being built on the stack.
+pub trait Dimension: private::Sealed {
+    const N: usize;
+
+    type Array<T>: AsRef<[T]> + AsMut<[T]> + IntoIterator<Item = T>;
+
+    fn try_from_fn<T>(f: impl FnMut(usize) -> Option<T>) -> Option<Self::Array<T>>;
+}
+
+pub struct Dim<const N: usize>;
+
+impl<const N: usize> private::Sealed for Dim<N> {}
+
+impl<const N: usize> Dimension for Dim<N> {
+    const N: usize = N;
+
+    type Array<T> = [T; N];
+
+    fn try_from_fn<T>(f: impl FnMut(usize) -> Option<T>) -> Option<Self::Array<T>> {
+        try_from_fn(f)
+    }
+}
+
+/// Fixed-size byte buffer holding a packed key or signature
+pub trait ByteArray: AsRef<[u8]> + AsMut<[u8]> + Copy + Eq + Debug + Send + Sync {
+    fn zeroed() -> Self;
+
+    /// Copies `bytes`, which must be exactly as large as the buffer
+    fn try_from_slice(bytes: &[u8]) -> Result<Self, Error>;
+}
+
+impl<const N: usize> ByteArray for [u8; N] {
+    fn zeroed() -> Self {
+        [0; N]
+    }
+
+    fn try_from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        bytes.try_into().map_err(|_| Error::WrongLength {
+            expected: N,
+            actual: bytes.len(),
+        })
+    }
+}
+
+/// Security level of the scheme
+///
+/// The sizes of keys and signatures are derived from the other parameters. Only the values
+/// defined by the Dilithium specification are supported: `ETA` must be 2 or 4, `GAMMA1` must be
+/// `1 << 17` or `1 << 19` and `GAMMA2` must be `(Q - 1) / 88` or `(Q - 1) / 32`.
+pub trait ParameterSet {
+    type K: Dimension;
+    type L: Dimension;
+
+    type PublicKeyBytes: ByteArray;
+    type SecretKeyBytes: ByteArray;
+    type SignatureBytes: ByteArray;
+
+    const ETA: Coefficient;
+    const TAU: usize;
+    const BETA: Coefficient;
+    const GAMMA1: Coefficient;
+    const GAMMA2: Coefficient;
+    const OMEGA: usize;
+
+    /// Size of the hash of the public key stored in the secret key
+    const TR_SIZE: usize = SEED_SIZE / 2;
+    /// Size of the seed of the challenge stored at the start of a signature
+    const CHALLENGE_SEED_SIZE: usize = SEED_SIZE / 2;
+
+    const ETA_PACKED_SIZE: usize = if Self::ETA == 2 { 96 } else { 128 };
+    const POLYZ_PACKED_SIZE: usize = if Self::GAMMA1 == 1 << 17 { 576 } else { 640 };
+    const POLYW1_PACKED_SIZE: usize = if Self::GAMMA2 == (Q - 1) / 88 {
+        192
+    } else {
+        128
+    };
+    const POLYVECH_PACKED_SIZE: usize = Self::OMEGA + Self::K::N;
+
+    const PUBLIC_KEY_SIZE: usize = SEED_SIZE / 2 + Self::K::N * T1_PACKED_SIZE;
+    const SECRET_KEY_SIZE: usize = SEED_SIZE
+        + Self::TR_SIZE
+        + Self::L::N * Self::ETA_PACKED_SIZE
+        + Self::K::N * Self::ETA_PACKED_SIZE
+        + Self::K::N * T0_PACKED_SIZE;
+    const SIGNATURE_SIZE: usize = Self::CHALLENGE_SEED_SIZE
+        + Self::L::N * Self::POLYZ_PACKED_SIZE
+        + Self::POLYVECH_PACKED_SIZE;
+}
+
+pub struct Dilithium2;
+
+impl ParameterSet for Dilithium2 {
+    type K = Dim<4>;
+    type L = Dim<4>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 2;
+    const TAU: usize = 39;
+    const BETA: Coefficient = 78;
+    const GAMMA1: Coefficient = 1 << 17;
+    const GAMMA2: Coefficient = (Q - 1) / 88;
+    const OMEGA: usize = 80;
+}
+
+pub struct Dilithium3;
+
+impl ParameterSet for Dilithium3 {
+    type K = Dim<6>;
+    type L = Dim<5>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 4;
+    const TAU: usize = 49;
+    const BETA: Coefficient = 196;
+    const GAMMA1: Coefficient = 1 << 19;
+    const GAMMA2: Coefficient = (Q - 1) / 32;
+    const OMEGA: usize = 55;
+}
+
+pub struct Dilithium5;
+
+impl ParameterSet for Dilithium5 {
+    type K = Dim<8>;
+    type L = Dim<7>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 2;
+    const TAU: usize = 60;
+    const BETA: Coefficient = 120;
+    const GAMMA1: Coefficient = 1 << 19;
+    const GAMMA2: Coefficient = (Q - 1) / 32;
+    const OMEGA: usize = 75;
+}
+
+/// ML-DSA-44 as standardized in FIPS 204
+pub struct MlDsa44;
+
+impl ParameterSet for MlDsa44 {
+    type K = Dim<4>;
+    type L = Dim<4>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 2;
+    const TAU: usize = 39;
+    const BETA: Coefficient = 78;
+    const GAMMA1: Coefficient = 1 << 17;
+    const GAMMA2: Coefficient = (Q - 1) / 88;
+    const OMEGA: usize = 80;
+
+    const TR_SIZE: usize = SEED_SIZE;
+    const CHALLENGE_SEED_SIZE: usize = 32;
+}
+
+/// ML-DSA-65 as standardized in FIPS 204
+pub struct MlDsa65;
+
+impl ParameterSet for MlDsa65 {
+    type K = Dim<6>;
+    type L = Dim<5>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 4;
+    const TAU: usize = 49;
+    const BETA: Coefficient = 196;
+    const GAMMA1: Coefficient = 1 << 19;
+    const GAMMA2: Coefficient = (Q - 1) / 32;
+    const OMEGA: usize = 55;
+
+    const TR_SIZE: usize = SEED_SIZE;
+    const CHALLENGE_SEED_SIZE: usize = 48;
+}
+
+/// ML-DSA-87 as standardized in FIPS 204
+pub struct MlDsa87;
+
+impl ParameterSet for MlDsa87 {
+    type K = Dim<8>;
+    type L = Dim<7>;
+
+    type PublicKeyBytes = [u8; Self::PUBLIC_KEY_SIZE];
+    type SecretKeyBytes = [u8; Self::SECRET_KEY_SIZE];
+    type SignatureBytes = [u8; Self::SIGNATURE_SIZE];
+
+    const ETA: Coefficient = 2;
+    const TAU: usize = 60;
+    const BETA: Coefficient = 120;
+    const GAMMA1: Coefficient = 1 << 19;
+    const GAMMA2: Coefficient = (Q - 1) / 32;
+    const OMEGA: usize = 75;
+
+    const TR_SIZE: usize = SEED_SIZE;
+    const CHALLENGE_SEED_SIZE: usize = 64;
+}
+
+mod private {
+    pub trait Sealed {}
+}
@@ -1,3 +1,8 @@
+//! Polynomials in plain and NTT representations
+//!
+//! Polynomials may hold secret vectors or values derived from them, so both representations wipe
+//! their coefficients when dropped. A `Vector` or a `Matrix` is wiped along with its polynomials.
+
 use crate::coefficient;
 
 pub mod ntt;
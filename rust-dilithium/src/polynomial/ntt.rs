@@ -1,7 +1,9 @@
 use crate::{
     array_math::{pointwise_add_inplace, pointwise_sub_inplace},
     coefficient::{self, Coefficient},
+    packing::Pack,
     polynomial::{plain::PlainPolynomial, Coefficients, ZETAS},
+    TryCollectArray,
 };
 use core::{
     iter::{zip, Sum},
@@ -10,36 +12,33 @@ use core::{
 };
 
 use super::NB_COEFFICIENTS;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct NTTPolynomial {
-    coefficients: Coefficients,
+    pub(super) coefficients: Coefficients,
 }
 
 impl NTTPolynomial {
     pub fn into_plain(mut self) -> PlainPolynomial {
-        let f = 41978;
-        let mut zeta_it = ZETAS.iter().rev();
-
-        for chunk_size in (0..8).map(|n| 1 << n) {
-            let double_chunks_it = self.coefficients.chunks_mut(2 * chunk_size);
-            for double_chunks in double_chunks_it {
-                let &zeta = zeta_it.next().unwrap();
-                let (lchunk, rchunk) = double_chunks.split_at_mut(chunk_size);
-                for (lx, rx) in zip(lchunk, rchunk) {
-                    let tmp = *lx;
-                    *lx = tmp + *rx;
-                    *rx = tmp - *rx;
-                    *rx = coefficient::reduce_montgomery(*rx as i64 * -zeta as i64);
-                }
-            }
-        }
+        inverse_ntt(&mut self.coefficients);
+        PlainPolynomial::from(self.coefficients)
+    }
 
-        for coeff in self.coefficients.iter_mut() {
-            *coeff = coefficient::reduce_montgomery(*coeff as i64 * f);
-        }
+    /// Writes the inverse NTT of the polynomial to `dst`, leaving the polynomial as is
+    pub fn plain_into(&self, dst: &mut PlainPolynomial) {
+        dst.coefficients = self.coefficients;
+        inverse_ntt(&mut dst.coefficients);
+    }
 
-        PlainPolynomial::from(self.coefficients)
+    /// Adds the product of `lhs` and `rhs`, as `self += lhs * rhs` does without the temporary
+    pub fn add_product_assign(&mut self, lhs: &Self, rhs: &Self) {
+        let products_it = zip(lhs.coefficients.iter(), rhs.coefficients.iter())
+            .map(|(&lhs_coeff, &rhs_coeff)| lhs_coeff as i64 * rhs_coeff as i64);
+
+        for (self_coeff, product) in zip(self.coefficients.iter_mut(), products_it) {
+            *self_coeff += coefficient::reduce_montgomery(product);
+        }
     }
 
     pub fn map_inplace(&mut self, f: &mut impl FnMut(Coefficient) -> Coefficient) {
@@ -70,6 +69,12 @@ impl AddAssign for NTTPolynomial {
     }
 }
 
+impl AddAssign<&Self> for NTTPolynomial {
+    fn add_assign(&mut self, rhs: &Self) {
+        pointwise_add_inplace(&mut self.coefficients, rhs.coefficients.iter().copied())
+    }
+}
+
 impl Sum for NTTPolynomial {
     fn sum<It: Iterator<Item = Self>>(it: It) -> Self {
         let init = Self::from([0; NB_COEFFICIENTS]);
@@ -92,6 +97,12 @@ impl SubAssign for NTTPolynomial {
     }
 }
 
+impl SubAssign<&Self> for NTTPolynomial {
+    fn sub_assign(&mut self, rhs: &Self) {
+        pointwise_sub_inplace(&mut self.coefficients, rhs.coefficients.iter().copied());
+    }
+}
+
 impl Mul for NTTPolynomial {
     type Output = Self;
 
@@ -125,6 +136,41 @@ impl MulAssign<&Self> for NTTPolynomial {
     }
 }
 
+impl Pack for NTTPolynomial {
+    type Input = Coefficient;
+    type Output = u8;
+
+    fn pack_inplace<const IN_N: usize, const OUT_N: usize>(
+        &self,
+        f: &impl Fn(&[Self::Input; IN_N]) -> [Self::Output; OUT_N],
+        output: &mut [Self::Output],
+    ) {
+        assert!(NB_COEFFICIENTS / IN_N == output.len() / OUT_N);
+        assert!(NB_COEFFICIENTS.is_multiple_of(IN_N));
+        assert!(output.len() % OUT_N == 0);
+
+        let coefficients_chunk_it = self.coefficients.chunks_exact(IN_N);
+        let output_chunk_it = output.chunks_exact_mut(OUT_N);
+
+        for (coeff_chunk, output_chunk) in zip(coefficients_chunk_it, output_chunk_it) {
+            // Unwrapping is safe here because out chunks are of the right size
+            output_chunk.copy_from_slice(&f(coeff_chunk.try_into().unwrap()));
+        }
+    }
+
+    fn unpack<const IN_N: usize, const OUT_N: usize>(
+        packed: &[Self::Output],
+        f: &impl Fn(&[Self::Output; OUT_N]) -> [Self::Input; IN_N],
+    ) -> Self {
+        let it = packed
+            .chunks_exact(OUT_N)
+            .flat_map(TryFrom::try_from)
+            .flat_map(f);
+
+        Self::from(it.try_collect_array().unwrap())
+    }
+}
+
 impl Default for NTTPolynomial {
     fn default() -> Self {
         Self::from([0; NB_COEFFICIENTS])
@@ -139,3 +185,41 @@ impl<'a> IntoIterator for &'a NTTPolynomial {
         self.coefficients.iter()
     }
 }
+
+impl Zeroize for NTTPolynomial {
+    fn zeroize(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl Drop for NTTPolynomial {
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+/// Inverse NTT, in place, leaving the coefficients out of the Montgomery domain
+fn inverse_ntt(coefficients: &mut Coefficients) {
+    let f = 41978;
+    let mut zeta_it = ZETAS.iter().rev();
+
+    for chunk_size in (0..8).map(|n| 1 << n) {
+        let double_chunks_it = coefficients.chunks_mut(2 * chunk_size);
+        for double_chunks in double_chunks_it {
+            let &zeta = zeta_it.next().unwrap();
+            let (lchunk, rchunk) = double_chunks.split_at_mut(chunk_size);
+            for (lx, rx) in zip(lchunk, rchunk) {
+                let tmp = *lx;
+                *lx = tmp + *rx;
+                *rx = tmp - *rx;
+                *rx = coefficient::reduce_montgomery(*rx as i64 * -zeta as i64);
+            }
+        }
+    }
+
+    for coeff in coefficients.iter_mut() {
+        *coeff = coefficient::reduce_montgomery(*coeff as i64 * f);
+    }
+}
+
+impl ZeroizeOnDrop for NTTPolynomial {}
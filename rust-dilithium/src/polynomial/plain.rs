@@ -12,49 +12,43 @@ use core::{
 };
 
 use super::NB_COEFFICIENTS;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct PlainPolynomial {
-    coefficients: Coefficients,
+    pub(super) coefficients: Coefficients,
 }
 
 impl PlainPolynomial {
     pub fn into_ntt(mut self) -> NTTPolynomial {
-        let mut zeta_it = ZETAS[1..].iter();
-
-        for chunk_size in (0..8).rev().map(|n| 1 << n) {
-            let double_chunks_it = self.coefficients.chunks_mut(2 * chunk_size);
-            for double_chunks in double_chunks_it {
-                let &zeta = zeta_it.next().unwrap();
-                let (lchunk, rchunk) = double_chunks.split_at_mut(chunk_size);
-                for (lx, rx) in zip(lchunk, rchunk) {
-                    let tmp = coefficient::reduce_montgomery(*rx as i64 * zeta as i64);
-                    *rx = *lx - tmp;
-                    *lx += tmp;
-                }
-            }
-        }
-
+        ntt(&mut self.coefficients);
         NTTPolynomial::from(self.coefficients)
     }
 
+    /// Writes the NTT of the polynomial to `dst`, leaving the polynomial as is
+    pub fn ntt_into(&self, dst: &mut NTTPolynomial) {
+        dst.coefficients = self.coefficients;
+        ntt(&mut dst.coefficients);
+    }
+
     pub fn map_inplace(&mut self, f: &mut impl FnMut(Coefficient) -> Coefficient) {
         for coeff in self.coefficients.iter_mut() {
             *coeff = f(*coeff);
         }
     }
 
-    pub fn map_fork(
+    /// Splits each coefficient in two with `f`, keeping the first part and writing the second one
+    /// to `other`
+    pub fn map_fork_into(
         &mut self,
+        other: &mut Self,
         f: &mut impl FnMut(Coefficient) -> (Coefficient, Coefficient),
-    ) -> Self {
-        let mut other_coefficients: Coefficients = [0; NB_COEFFICIENTS];
+    ) {
         for (self_coeff, other_coeff) in
-            zip(self.coefficients.iter_mut(), other_coefficients.iter_mut())
+            zip(self.coefficients.iter_mut(), other.coefficients.iter_mut())
         {
             (*self_coeff, *other_coeff) = f(*self_coeff);
         }
-        Self::from(other_coefficients)
     }
 
     pub fn max(&self) -> Coefficient {
@@ -62,8 +56,7 @@ impl PlainPolynomial {
             .iter()
             .copied()
             .map(coefficient::abs)
-            .max()
-            .unwrap()
+            .fold(0, coefficient::max)
     }
 
     pub fn dump(&self) -> [i64; NB_COEFFICIENTS] {
@@ -92,6 +85,12 @@ impl AddAssign for PlainPolynomial {
     }
 }
 
+impl AddAssign<&Self> for PlainPolynomial {
+    fn add_assign(&mut self, rhs: &Self) {
+        pointwise_add_inplace(&mut self.coefficients, rhs.coefficients.iter().copied())
+    }
+}
+
 impl Sub for PlainPolynomial {
     type Output = Self;
 
@@ -107,6 +106,12 @@ impl SubAssign for PlainPolynomial {
     }
 }
 
+impl SubAssign<&Self> for PlainPolynomial {
+    fn sub_assign(&mut self, rhs: &Self) {
+        pointwise_sub_inplace(&mut self.coefficients, rhs.coefficients.iter().copied());
+    }
+}
+
 impl Pack for PlainPolynomial {
     type Input = Coefficient;
     type Output = u8;
@@ -117,7 +122,7 @@ impl Pack for PlainPolynomial {
         output: &mut [Self::Output],
     ) {
         assert!(NB_COEFFICIENTS / IN_N == output.len() / OUT_N);
-        assert!(NB_COEFFICIENTS % IN_N == 0);
+        assert!(NB_COEFFICIENTS.is_multiple_of(IN_N));
         assert!(output.len() % OUT_N == 0);
 
         let coefficients_chunk_it = self.coefficients.chunks_exact(IN_N);
@@ -158,3 +163,35 @@ impl<'a> IntoIterator for &'a PlainPolynomial {
         self.coefficients.iter()
     }
 }
+
+impl Zeroize for PlainPolynomial {
+    fn zeroize(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+impl Drop for PlainPolynomial {
+    fn drop(&mut self) {
+        self.coefficients.zeroize();
+    }
+}
+
+/// Forward NTT, in place
+fn ntt(coefficients: &mut Coefficients) {
+    let mut zeta_it = ZETAS[1..].iter();
+
+    for chunk_size in (0..8).rev().map(|n| 1 << n) {
+        let double_chunks_it = coefficients.chunks_mut(2 * chunk_size);
+        for double_chunks in double_chunks_it {
+            let &zeta = zeta_it.next().unwrap();
+            let (lchunk, rchunk) = double_chunks.split_at_mut(chunk_size);
+            for (lx, rx) in zip(lchunk, rchunk) {
+                let tmp = coefficient::reduce_montgomery(*rx as i64 * zeta as i64);
+                *rx = *lx - tmp;
+                *lx += tmp;
+            }
+        }
+    }
+}
+
+impl ZeroizeOnDrop for PlainPolynomial {}
@@ -0,0 +1,232 @@
+//! Keys implementing the traits of the RustCrypto `signature` crate
+//!
+//! The functions of the crate take the counter that expands the matrix and the vectors as a type
+//! parameter, which the traits have no room for. [`SigningKey`] and [`VerifyingKey`] bind a key
+//! to its counter, so that generic code can sign and verify through [`Signer`],
+//! [`RandomizedSigner`] and [`Verifier`]. Signing with [`Signer`] is deterministic as [`sign`]
+//! is, and signing with [`RandomizedSigner`] is hedged as [`sign_with_rng`] is.
+//!
+//! The expanded keys implement the same traits, and the ML-DSA keys are found in
+//! [`crate::mldsa`].
+//!
+//! [`sign`]: crate::sign
+//! [`sign_with_rng`]: crate::sign_with_rng
+
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+
+use rand_core::CryptoRngCore;
+use signature::{Keypair, RandomizedSigner, SignatureEncoding, Signer, Verifier};
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    counter::Counter,
+    expanded::{ExpandedSigningKey, ExpandedVerifyingKey},
+    make_keys, sign, sign_with_rng, verify, CompactSecretKey, Error, KeyPair, ParameterSet,
+    PublicKey, SecretKey, Seed, Signature, RANDOMNESS_SIZE,
+};
+
+/// Key pair bound to the counter `Ctr`, which signs through the `signature` traits
+pub struct SigningKey<P: ParameterSet, Ctr: Counter> {
+    key_pair: KeyPair<P>,
+    /// Seed of the key pair, when it was derived from one
+    compact_secret_key: Option<CompactSecretKey<P>>,
+    phantom: PhantomData<Ctr>,
+}
+
+/// Public key bound to the counter `Ctr`, which verifies through the `signature` traits
+pub struct VerifyingKey<P: ParameterSet, Ctr: Counter> {
+    public_key: PublicKey<P>,
+    phantom: PhantomData<Ctr>,
+}
+
+impl<P: ParameterSet, Ctr: Counter> SigningKey<P, Ctr> {
+    /// Binds `key_pair`, which must have been made with the counter `Ctr`
+    pub fn new(key_pair: KeyPair<P>) -> Self {
+        Self {
+            key_pair,
+            compact_secret_key: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Derives the key pair of `seed` as [`make_keys`] does, and keeps the seed
+    pub fn from_seed(seed: &Seed) -> Result<Self, Error> {
+        let mut retval = make_keys::<P, Ctr>(seed).map(|key_pair| Self::new(key_pair.into()))?;
+        retval.compact_secret_key = Some(CompactSecretKey::new(seed));
+
+        Ok(retval)
+    }
+
+    /// Expands `sk`, which must have been made with the counter `Ctr`, and keeps it
+    pub fn from_compact_secret_key(sk: &CompactSecretKey<P>) -> Result<Self, Error> {
+        Self::from_seed(sk.seed())
+    }
+
+    pub fn key_pair(&self) -> &KeyPair<P> {
+        &self.key_pair
+    }
+
+    pub fn public_key(&self) -> &PublicKey<P> {
+        self.key_pair.public_key()
+    }
+
+    pub fn secret_key(&self) -> &SecretKey<P> {
+        self.key_pair.secret_key()
+    }
+
+    /// Returns the seed of the key pair, unless the key was bound without it
+    pub fn compact_secret_key(&self) -> Option<&CompactSecretKey<P>> {
+        self.compact_secret_key.as_ref()
+    }
+
+    pub fn into_key_pair(self) -> KeyPair<P> {
+        self.key_pair
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> VerifyingKey<P, Ctr> {
+    /// Binds `public_key`, which must have been made with the counter `Ctr`
+    pub fn new(public_key: PublicKey<P>) -> Self {
+        Self {
+            public_key,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey<P> {
+        &self.public_key
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.public_key.as_bytes()
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Signer<Signature<P>> for SigningKey<P, Ctr> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+        Ok(sign::<P, Ctr>(msg, self.secret_key())?)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> RandomizedSigner<Signature<P>> for SigningKey<P, Ctr> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<P>, signature::Error> {
+        Ok(sign_with_rng::<P, Ctr>(msg, self.secret_key(), rng)?)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Keypair for SigningKey<P, Ctr> {
+    type VerifyingKey = VerifyingKey<P, Ctr>;
+
+    fn verifying_key(&self) -> VerifyingKey<P, Ctr> {
+        VerifyingKey::new(*self.public_key())
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Verifier<Signature<P>> for VerifyingKey<P, Ctr> {
+    fn verify(&self, msg: &[u8], signature: &Signature<P>) -> Result<(), signature::Error> {
+        Ok(verify::<P, Ctr>(msg, signature, &self.public_key)?)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Signer<Signature<P>> for ExpandedSigningKey<P, Ctr> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+        Ok(ExpandedSigningKey::sign(self, msg)?)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> RandomizedSigner<Signature<P>> for ExpandedSigningKey<P, Ctr> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature<P>, signature::Error> {
+        let mut rnd = Zeroizing::new([0u8; RANDOMNESS_SIZE]);
+        rng.fill_bytes(rnd.as_mut());
+
+        Ok(self.sign_hedged(msg, &rnd)?)
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Verifier<Signature<P>> for ExpandedVerifyingKey<P, Ctr> {
+    fn verify(&self, msg: &[u8], signature: &Signature<P>) -> Result<(), signature::Error> {
+        Ok(ExpandedVerifyingKey::verify(self, msg, signature)?)
+    }
+}
+
+/// The signature is its own byte representation, since it is stored packed
+impl<P: ParameterSet + 'static> SignatureEncoding for Signature<P> {
+    type Repr = Self;
+}
+
+impl From<Error> for signature::Error {
+    fn from(_: Error) -> Self {
+        // The reason cannot be kept as a source without `std`
+        signature::Error::new()
+    }
+}
+
+// The traits are implemented by hand since deriving them would require the parameter set and the
+// counter themselves to implement them
+
+impl<P: ParameterSet, Ctr: Counter> Clone for SigningKey<P, Ctr> {
+    fn clone(&self) -> Self {
+        Self {
+            key_pair: self.key_pair.clone(),
+            compact_secret_key: self.compact_secret_key.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Debug for SigningKey<P, Ctr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("key_pair", &self.key_pair)
+            .finish()
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> ZeroizeOnDrop for SigningKey<P, Ctr> {}
+
+impl<P: ParameterSet, Ctr: Counter> Clone for VerifyingKey<P, Ctr> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Copy for VerifyingKey<P, Ctr> {}
+
+impl<P: ParameterSet, Ctr: Counter> PartialEq for VerifyingKey<P, Ctr> {
+    fn eq(&self, other: &Self) -> bool {
+        self.public_key == other.public_key
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> Eq for VerifyingKey<P, Ctr> {}
+
+impl<P: ParameterSet, Ctr: Counter> Debug for VerifyingKey<P, Ctr> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyingKey")
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> AsRef<[u8]> for VerifyingKey<P, Ctr> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<P: ParameterSet, Ctr: Counter> From<PublicKey<P>> for VerifyingKey<P, Ctr> {
+    fn from(public_key: PublicKey<P>) -> Self {
+        Self::new(public_key)
+    }
+}
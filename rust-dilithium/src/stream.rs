@@ -0,0 +1,93 @@
+//! Incremental signing and verification of messages that are not available as a single slice
+//!
+//! The chunks of the message are absorbed as they arrive, so the message never has to be held in
+//! memory as a whole. The signatures are the same as those of [`sign`] and [`verify`].
+//!
+//! [`sign`]: crate::sign
+//! [`verify`]: crate::verify
+
+use core::{marker::PhantomData, mem::size_of};
+
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use crate::{
+    counter::Counter, make_tr, secret_key_partition_sizes, sign_internal, verify_internal, Error,
+    Mu, ParameterSet, Partition, PublicKey, SecretKey, Signature, RANDOMNESS_SIZE,
+};
+
+pub struct Signer<P: ParameterSet, Ctr: Counter> {
+    hasher: Shake256,
+    phantom: PhantomData<(P, Ctr)>,
+}
+
+impl<P: ParameterSet, Ctr: Counter> Signer<P, Ctr> {
+    /// Starts signing a message with `sk`, which must also be passed to the finalization
+    pub fn new(sk: &SecretKey<P>) -> Self {
+        let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+        let mut hasher = Shake256::default();
+        hasher.update(tr);
+
+        Self {
+            hasher,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finalize_sign(self, sk: &SecretKey<P>) -> Result<Signature<P>, Error> {
+        Ok(sign_internal::<P, Ctr>(&finalize_mu(self.hasher), &[], sk))
+    }
+
+    /// Signs the message in the hedged variant, where `rnd` must be fresh randomness
+    pub fn finalize_sign_hedged(
+        self,
+        sk: &SecretKey<P>,
+        rnd: &[u8; RANDOMNESS_SIZE],
+    ) -> Result<Signature<P>, Error> {
+        Ok(sign_internal::<P, Ctr>(&finalize_mu(self.hasher), rnd, sk))
+    }
+}
+
+pub struct Verifier<P: ParameterSet, Ctr: Counter> {
+    hasher: Shake256,
+    phantom: PhantomData<(P, Ctr)>,
+}
+
+impl<P: ParameterSet, Ctr: Counter> Verifier<P, Ctr> {
+    /// Starts verifying a message against `pk`, which must also be passed to the finalization
+    pub fn new(pk: &PublicKey<P>) -> Self {
+        let tr = make_tr::<P>(pk);
+
+        let mut hasher = Shake256::default();
+        hasher.update(&tr[..P::TR_SIZE]);
+
+        Self {
+            hasher,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finalize_verify(self, signature: &Signature<P>, pk: &PublicKey<P>) -> Result<(), Error> {
+        verify_internal::<P, Ctr>(&finalize_mu(self.hasher), signature, pk)
+    }
+}
+
+fn finalize_mu(hasher: Shake256) -> Mu {
+    let mut retval = [0u8; size_of::<Mu>()];
+
+    let mut reader = hasher.finalize_xof();
+    reader.read(&mut retval);
+
+    retval
+}
@@ -0,0 +1,95 @@
+//! Test vectors of the ML-DSA keyGen, sigGen and sigVer algorithms from the NIST ACVP server
+//!
+//! The `internalProjection.json` files of the server, which hold the inputs of the test cases
+//! along with their expected results, are fetched to `rsrc/acvp` by `make acvp`. Only the first
+//! test cases of each test group are run, and each group covers a parameter set and a variant:
+//! deterministic or hedged signing, the pure or pre-hash variants of the external interface, or
+//! the internal interface with or without an external `mu`. The hash functions that HashML-DSA
+//! does not support here are skipped.
+
+use crate::{mldsa::PreHash, Mu, SEED_SIZE};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake128, Shake256,
+};
+use std::fs::File;
+
+/// Number of test cases run from each test group
+const TESTS_PER_GROUP: usize = 3;
+
+/// Returns the test groups of the parameter set `parameter_set` from the vectors of `algorithm`,
+/// each with the test cases to run
+pub fn test_groups(algorithm: &str, parameter_set: &str) -> Vec<(Value, Vec<Value>)> {
+    let path = format!("rsrc/acvp/{algorithm}.json");
+    let vectors: Value = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+
+    vectors["testGroups"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|group| group["parameterSet"] == parameter_set)
+        .map(|group| {
+            let tests = group["tests"].as_array().unwrap();
+            let tests = tests.iter().take(TESTS_PER_GROUP).cloned().collect();
+            (group.clone(), tests)
+        })
+        .collect()
+}
+
+/// Decodes the hexadecimal field `name` of `test`, which some versions of the server hold in its
+/// test group instead
+pub fn bytes(group: &Value, test: &Value, name: &str) -> Vec<u8> {
+    let hex = test[name]
+        .as_str()
+        .or_else(|| group[name].as_str())
+        .unwrap_or_else(|| panic!("missing field {name}"));
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Calls `f` with the digest of `msg` by the hash function `hash_alg` of a HashML-DSA test case,
+/// unless it is not supported
+pub fn with_pre_hash<R>(hash_alg: &str, msg: &[u8], f: impl FnOnce(PreHash) -> R) -> Option<R> {
+    match hash_alg {
+        "SHA2-256" => Some(f(PreHash::Sha256(&Sha256::digest(msg).into()))),
+        "SHA2-512" => Some(f(PreHash::Sha512(&Sha512::digest(msg).into()))),
+        "SHAKE-128" => {
+            let mut digest = [0; 32];
+            Shake128::default()
+                .chain(msg)
+                .finalize_xof()
+                .read(&mut digest);
+            Some(f(PreHash::Shake128(&digest)))
+        }
+        _ => None,
+    }
+}
+
+/// Computes the `mu` of the message `msg` of the internal interface, given the hash `tr` of the
+/// public key
+pub fn mu(tr: &[u8], msg: &[u8]) -> Mu {
+    let mut retval = [0; SEED_SIZE];
+    Shake256::default()
+        .chain(tr)
+        .chain(msg)
+        .finalize_xof()
+        .read(&mut retval);
+
+    retval
+}
+
+/// Hashes the public key `pk` to its `tr`
+pub fn tr(pk: &[u8]) -> [u8; SEED_SIZE] {
+    let mut retval = [0; SEED_SIZE];
+    Shake256::default()
+        .chain(pk)
+        .finalize_xof()
+        .read(&mut retval);
+
+    retval
+}
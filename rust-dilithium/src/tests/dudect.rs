@@ -0,0 +1,178 @@
+//! Timing leakage detection in the style of dudect
+//!
+//! Each routine is run on inputs drawn from two classes, a fixed secret and random secrets, in a
+//! random order. The running times of both classes are compared with Welch's t-test, and a large
+//! t-statistic means that the running time depends on the secret. Measurements are only meaningful
+//! with optimizations, so these tests are ignored by default and run with:
+//!
+//! `cargo test --release dudect -- --ignored --nocapture`
+
+use std::{hint::black_box, time::Instant};
+
+use rand_core::RngCore;
+
+use super::ShakeRng;
+use crate::{
+    coefficient::{self, Coefficient},
+    counter::ShakeCounter,
+    make_keys,
+    polynomial::plain::PlainPolynomial,
+    sign_hedged, Dilithium2, Dilithium5, ParameterSet, SecretKey, Q, SEED_SIZE,
+};
+
+/// Above this bound, the running time depends on the class of the input beyond doubt
+const T_STATISTIC_MAX: f64 = 10.0;
+/// Fraction of the slowest measurements that are discarded as noise from the rest of the system
+const CROPPED_FRACTION: f64 = 0.05;
+
+#[derive(Default)]
+struct WelchTest {
+    counts: [f64; 2],
+    means: [f64; 2],
+    m2s: [f64; 2],
+}
+
+impl WelchTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.counts[class] += 1.0;
+
+        let delta = x - self.means[class];
+        self.means[class] += delta / self.counts[class];
+        self.m2s[class] += delta * (x - self.means[class]);
+    }
+
+    fn t_statistic(&self) -> f64 {
+        let [var0, var1] = [0, 1].map(|class| self.m2s[class] / (self.counts[class] - 1.0));
+
+        (self.means[0] - self.means[1]) / (var0 / self.counts[0] + var1 / self.counts[1]).sqrt()
+    }
+}
+
+/// Measures `f` on `measurements_nb` inputs made by `make_input`, which is given the class of the
+/// input, and returns the t-statistic
+fn measure<T>(
+    measurements_nb: usize,
+    mut make_input: impl FnMut(usize, &mut ShakeRng) -> T,
+    mut f: impl FnMut(&T),
+) -> f64 {
+    let mut rng = ShakeRng::new(0xdd);
+
+    // Inputs are made beforehand so that only `f` is timed
+    let inputs: Vec<_> = (0..measurements_nb)
+        .map(|_| {
+            let class = (rng.next_u32() & 1) as usize;
+            (class, make_input(class, &mut rng))
+        })
+        .collect();
+
+    let durations: Vec<_> = inputs
+        .iter()
+        .map(|(class, input)| {
+            let start = Instant::now();
+            f(black_box(input));
+            (*class, start.elapsed().as_nanos() as f64)
+        })
+        .collect();
+
+    let mut sorted_durations: Vec<_> = durations.iter().map(|&(_, duration)| duration).collect();
+    sorted_durations.sort_by(f64::total_cmp);
+    let threshold = sorted_durations[((1.0 - CROPPED_FRACTION) * measurements_nb as f64) as usize];
+
+    let mut test = WelchTest::default();
+
+    for (class, duration) in durations {
+        if duration <= threshold {
+            test.push(class, duration);
+        }
+    }
+
+    test.t_statistic()
+}
+
+fn check(name: &str, t_statistic: f64) {
+    println!("{name}: t = {t_statistic:.2}");
+    assert!(t_statistic.abs() < T_STATISTIC_MAX);
+}
+
+fn random_coefficients(rng: &mut ShakeRng, bound: Coefficient) -> [Coefficient; 256] {
+    [0; 256].map(|_| (rng.next_u32() % (2 * bound as u32 + 1)) as Coefficient - bound)
+}
+
+#[test]
+#[ignore]
+fn dudect_coefficient_routines() {
+    const MEASUREMENTS_NB: usize = 200000;
+
+    let gamma2 = <Dilithium2 as ParameterSet>::GAMMA2;
+    let make_input = |bound| {
+        move |class, rng: &mut ShakeRng| match class {
+            0 => [-1; 256],
+            _ => random_coefficients(rng, bound),
+        }
+    };
+
+    check(
+        "caddq",
+        measure(MEASUREMENTS_NB, make_input(Q - 1), |input| {
+            black_box(input.map(coefficient::caddq));
+        }),
+    );
+
+    check(
+        "max",
+        measure(MEASUREMENTS_NB, make_input(Q - 1), |input| {
+            black_box(PlainPolynomial::from(*input).max());
+        }),
+    );
+
+    check(
+        "make_hint",
+        measure(MEASUREMENTS_NB, make_input(2 * gamma2), |input| {
+            black_box(input.map(|n0| coefficient::make_hint::<Dilithium2>(n0, n0 & 1)));
+        }),
+    );
+}
+
+#[test]
+#[ignore]
+fn dudect_sign() {
+    const MEASUREMENTS_NB: usize = 4000;
+    const KEYS_NB: usize = 32;
+
+    fn check_parameter_set<P: ParameterSet>(name: &str) {
+        let mut rng = ShakeRng::new(0x5e);
+        let secret_keys: Vec<SecretKey<P>> = (0..KEYS_NB)
+            .map(|_| {
+                let mut seed = [0; SEED_SIZE / 2];
+                rng.fill_bytes(&mut seed);
+                make_keys::<P, ShakeCounter>(&seed).unwrap().1
+            })
+            .collect();
+
+        // The randomness is drawn in both classes, so that both go through the same distribution of
+        // rejected iterations, and only the secret key tells them apart
+        let make_input = |class, rng: &mut ShakeRng| {
+            let key_index = match class {
+                0 => 0,
+                _ => 1 + rng.next_u32() as usize % (KEYS_NB - 1),
+            };
+            let mut rnd = [0; 32];
+            rng.fill_bytes(&mut rnd);
+
+            (key_index, rnd)
+        };
+
+        check(
+            name,
+            measure(MEASUREMENTS_NB, make_input, |(key_index, rnd)| {
+                black_box(
+                    sign_hedged::<P, ShakeCounter>(b"message", &secret_keys[*key_index], rnd)
+                        .unwrap(),
+                );
+            }),
+        );
+    }
+
+    check_parameter_set::<Dilithium2>("sign (Dilithium2)");
+    check_parameter_set::<Dilithium5>("sign (Dilithium5)");
+}
@@ -1,8 +1,9 @@
 use crate::{
     coefficient,
+    params::Dim,
     polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial},
     vector::{Matrix, Vector},
-    ArrayChunks, TryCollectArray, K, L, SEED_SIZE,
+    ArrayChunks, Dilithium5, ParameterSet, TryCollectArray, SEED_SIZE,
 };
 
 use nom::{
@@ -15,11 +16,16 @@ use nom::{
 use std::{
     fs::File,
     io::{BufReader, Read},
+    ptr::addr_of,
 };
 
+type K = <Dilithium5 as ParameterSet>::K;
+type L = <Dilithium5 as ParameterSet>::L;
+
 const N: u32 = 25;
 const FIXTURE_TEXT_SIZE_MAX: u32 = 1000000;
 static mut FIXTURES: Vec<Fixture> = Vec::new();
+static mut SHAKE_FIXTURES: Vec<Fixture> = Vec::new();
 
 #[derive(PartialEq, Debug)]
 pub struct Fixture {
@@ -46,18 +52,27 @@ impl Fixture {
 }
 
 pub fn fixtures() -> &'static Vec<Fixture> {
-    unsafe { &FIXTURES }
+    unsafe { &*addr_of!(FIXTURES) }
+}
+
+pub fn shake_fixtures() -> &'static Vec<Fixture> {
+    unsafe { &*addr_of!(SHAKE_FIXTURES) }
 }
 
 #[ctor::ctor]
 unsafe fn make_fixtures() {
-    let mut buf = BufReader::new(File::open("rsrc/fixtures.txt").unwrap())
-        .take(u64::from(N * FIXTURE_TEXT_SIZE_MAX));
+    FIXTURES = load_fixtures("rsrc/fixtures.txt");
+    SHAKE_FIXTURES = load_fixtures("rsrc/fixtures-shake.txt");
+}
+
+fn load_fixtures(path: &str) -> Vec<Fixture> {
+    let mut buf =
+        BufReader::new(File::open(path).unwrap()).take(u64::from(N * FIXTURE_TEXT_SIZE_MAX));
     let mut s = String::new();
 
     buf.read_to_string(&mut s).unwrap();
 
-    FIXTURES = (0..N)
+    let fixtures: Vec<_> = (0..N)
         .scan(s.as_str(), |s, _| {
             let (remainder, fixture) = parse_fixture(s).ok()?;
             *s = remainder;
@@ -65,7 +80,9 @@ unsafe fn make_fixtures() {
         })
         .collect();
 
-    assert!((0..N).eq(FIXTURES.iter().map(|fixture| fixture.count)));
+    assert!((0..N).eq(fixtures.iter().map(|fixture| fixture.count)));
+
+    fixtures
 }
 
 fn parse_fixture(s: &str) -> IResult<&str, Fixture> {
@@ -125,7 +142,7 @@ fn parse_fixture(s: &str) -> IResult<&str, Fixture> {
     let (s, c) = take_until("\n\n")(s)?;
     let (s, _) = take(2u8)(s)?;
 
-    let count = u32::from_str_radix(count, 10).unwrap();
+    let count = count.parse().unwrap();
     let m = parse_byte_vector(m)?.1;
     let pk = parse_byte_vector(pk)?.1.try_into().unwrap();
     let sk = parse_byte_vector(sk)?.1.try_into().unwrap();
@@ -141,7 +158,7 @@ fn parse_fixture(s: &str) -> IResult<&str, Fixture> {
     let c = parse_ones_vector(c)?.1;
 
     Ok((
-        &s,
+        s,
         Fixture {
             count,
             m,
@@ -206,7 +223,7 @@ fn parse_bracket_lists(s: &str) -> IResult<&str, Vec<Vec<&str>>> {
     separated_list0(tag(", "), parse_bracket_list)(s)
 }
 
-fn parse_poly_list<const N: usize>(s: &str) -> IResult<&str, Vector<PlainPolynomial, N>> {
+fn parse_poly_list<const N: usize>(s: &str) -> IResult<&str, Vector<PlainPolynomial, Dim<N>>> {
     let (s, char_vec) = delimited(
         char('('),
         separated_list0(pair(tag(",\n"), take_while(is_space)), parse_bracket_list),
@@ -244,5 +261,5 @@ fn is_space(c: char) -> bool {
 }
 
 fn is_minus_or_digit(c: char) -> bool {
-    c.is_digit(10) || c == '-'
+    c.is_ascii_digit() || c == '-'
 }
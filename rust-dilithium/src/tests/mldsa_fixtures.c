@@ -0,0 +1,124 @@
+/*
+ * Prints the digests of mldsa_fixtures.rs for the parameter set MLD_CONFIG_PARAMETER_SET, as
+ * computed by mldsa-native (https://github.com/pq-code-package/mldsa-native), see the
+ * `mldsa-fixtures` target of the Makefile
+ *
+ * The inputs of each test case are derived from its index as by the functions of
+ * mldsa_fixtures.rs, and the keys and signatures are hashed with SHAKE256.
+ */
+
+/* The whole library is built in this unit, which also gives access to its SHAKE256 */
+#include "mldsa_native.c"
+#include "mldsa_native.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CAT(a, b, c) a##b##_##c
+#define XCAT(a, b, c) CAT(a, b, c)
+#define API(f) XCAT(PQCP_MLDSA_NATIVE_MLDSA, MLD_CONFIG_PARAMETER_SET, f)
+
+#define CASES 6
+#define PK_SIZE MLDSA_PUBLICKEYBYTES(MLD_CONFIG_PARAMETER_SET)
+#define SK_SIZE MLDSA_SECRETKEYBYTES(MLD_CONFIG_PARAMETER_SET)
+#define SIG_SIZE MLDSA_BYTES(MLD_CONFIG_PARAMETER_SET)
+
+/* Only the functions taking their randomness as an argument are called */
+int randombytes(uint8_t *out, size_t outlen) {
+  memset(out, 0, outlen);
+  return 0;
+}
+
+struct inputs {
+  uint8_t seed[32], rnd[32], msg[256], ctx[255], digest[64];
+  size_t msg_len, ctx_len;
+};
+
+static void make_inputs(struct inputs *in, int i) {
+  size_t j;
+
+  for (j = 0; j < 32; j++) {
+    in->seed[j] = (uint8_t)(i * 32 + j);
+    in->rnd[j] = i % 2 ? (uint8_t)(i * 3 + j * 11) : 0;
+  }
+
+  in->msg_len = i * 13 + 1;
+  for (j = 0; j < in->msg_len; j++) {
+    in->msg[j] = (uint8_t)(j * 7 + i);
+  }
+
+  in->ctx_len = i * 5;
+  for (j = 0; j < in->ctx_len; j++) {
+    in->ctx[j] = (uint8_t)(j ^ 0x5a);
+  }
+
+  for (j = 0; j < sizeof in->digest; j++) {
+    in->digest[j] = (uint8_t)(j * 5 + i);
+  }
+}
+
+static void print_digest(const char *indent, const uint8_t *bytes, size_t len) {
+  uint8_t digest[32];
+  size_t j;
+
+  API(shake256)(digest, sizeof digest, bytes, len);
+
+  printf("%s\"", indent);
+  for (j = 0; j < sizeof digest; j++) {
+    printf("%02x", digest[j]);
+  }
+  printf("\",\n");
+}
+
+int main(void) {
+  static const int hash_algs[3] = {MLD_PREHASH_SHA2_256, MLD_PREHASH_SHA2_512,
+                                   MLD_PREHASH_SHAKE_128};
+  static const size_t digest_lens[3] = {32, 64, 32};
+  uint8_t pk[PK_SIZE], sk[SK_SIZE], sig[SIG_SIZE], prefix[257];
+  struct inputs in;
+  int i;
+
+  printf("pub const MLDSA%d_HASHES: [[&str; 3]; %d] = [\n", MLD_CONFIG_PARAMETER_SET, CASES);
+  for (i = 0; i < CASES; i++) {
+    make_inputs(&in, i);
+
+    /* The pure variant signs `0 || len(ctx) || ctx || msg` */
+    prefix[0] = 0;
+    prefix[1] = (uint8_t)in.ctx_len;
+    memcpy(prefix + 2, in.ctx, in.ctx_len);
+
+    if (API(keypair_internal)(pk, sk, in.seed) ||
+        API(signature_internal)(sig, in.msg, in.msg_len, prefix, in.ctx_len + 2, in.rnd, sk, 0) ||
+        API(verify)(sig, in.msg, in.msg_len, in.ctx, in.ctx_len, pk)) {
+      return 1;
+    }
+
+    printf("    [\n");
+    print_digest("        ", pk, sizeof pk);
+    print_digest("        ", sk, sizeof sk);
+    print_digest("        ", sig, sizeof sig);
+    printf("    ],\n");
+  }
+  printf("];\n\n");
+
+  printf("pub const HASH_MLDSA%d_SIGNATURE_HASHES: [&str; %d] = [\n", MLD_CONFIG_PARAMETER_SET,
+         CASES);
+  for (i = 0; i < CASES; i++) {
+    int alg = hash_algs[i % 3];
+    size_t digest_len = digest_lens[i % 3];
+
+    make_inputs(&in, i);
+
+    if (API(keypair_internal)(pk, sk, in.seed) ||
+        API(signature_pre_hash_internal)(sig, in.digest, digest_len, in.ctx, in.ctx_len, in.rnd,
+                                         sk, alg) ||
+        API(verify_pre_hash_internal)(sig, in.digest, digest_len, in.ctx, in.ctx_len, pk, alg)) {
+      return 1;
+    }
+
+    print_digest("    ", sig, sizeof sig);
+  }
+  printf("];\n");
+
+  return 0;
+}
@@ -0,0 +1,169 @@
+//! Hashes of the keys and signatures produced by mldsa-native v2.0.0, the ML-DSA implementation of
+//! the Post-Quantum Cryptography Alliance, which is independent of this crate
+//!
+//! The inputs of each test case are derived from its index by the functions below, and the
+//! outputs are hashed with SHAKE256 to a 32-bytes digest. Odd test cases are hedged while even
+//! ones are deterministic. The HashML-DSA test cases use SHA-256, SHA-512 and SHAKE128 in turn.
+//!
+//! The constants are printed by `mldsa_fixtures.c` with `make mldsa-fixtures`, which fetches
+//! mldsa-native and builds the generator against each parameter set.
+
+use crate::{Seed, RANDOMNESS_SIZE};
+
+pub fn seed(i: usize) -> Seed {
+    core::array::from_fn(|j| (i * 32 + j) as u8)
+}
+
+pub fn msg(i: usize) -> Vec<u8> {
+    (0..i * 13 + 1).map(|j| (j * 7 + i) as u8).collect()
+}
+
+pub fn ctx(i: usize) -> Vec<u8> {
+    (0..i * 5).map(|j| j as u8 ^ 0x5a).collect()
+}
+
+/// Digest signed in the HashML-DSA test cases, whose length depends on the hash function
+pub fn digest<const N: usize>(i: usize) -> [u8; N] {
+    core::array::from_fn(|j| (j * 5 + i) as u8)
+}
+
+pub fn rnd(i: usize) -> [u8; RANDOMNESS_SIZE] {
+    core::array::from_fn(|j| {
+        if i % 2 == 1 {
+            (i * 3 + j * 11) as u8
+        } else {
+            0
+        }
+    })
+}
+
+/// Decodes one of the hexadecimal hashes below
+pub fn hash(hex: &str) -> [u8; 32] {
+    core::array::from_fn(|j| u8::from_str_radix(&hex[j * 2..][..2], 16).unwrap())
+}
+
+pub const MLDSA44_HASHES: [[&str; 3]; 6] = [
+    [
+        "329a07b1fabb48f52a309f11a1898f848e2322ffe623ec810db3bee33685854a",
+        "eaa4bd0a7064e1aef9623bfa4c0209a98884f08558bbe934daefb16a049ba28a",
+        "1fc6c65d0644ee272aa99d8ba41d73f4f105596ce5cdfb814a4777a89bb9a01c",
+    ],
+    [
+        "f4de9678acf48a8606c0f2a477f45aaa658b35c2c0dd5b6322c517ae0a7b3180",
+        "4dc38a07c2122b3d524eae884d6760dd82ca580fa9cafa1655439fa2004feee5",
+        "ae613cb24e6f1558041d79d1faa31197e2fe5fe178ace6e8e28022d6965df7a5",
+    ],
+    [
+        "c99e51c362e9a1e6fc6721dc9e6dcb7f99a57fb62ed3cad23b025084f33098f3",
+        "b9844a78a8d6f3d83153608d96528c344c19242438b1f89b4c531f09daf1810b",
+        "2c9915d06cd626ef1032512b04ca5ac4e6642df20d9b9ba1ae14f09d3b7bc97b",
+    ],
+    [
+        "3954fd41817ea6510fd3d9d316d4ae676f4d2f335f200a09981808791860b9e5",
+        "039d21ba4730855b44c91457b705d262950dac8ae927bb4c14077e656a73406e",
+        "fc768b146f0b1940a147c8f38a875f4f4388a765948aa6afc53129f708c1a40d",
+    ],
+    [
+        "24268c2b2f50e6416e2014b3ea3e4d82ef3b0723d901d116a801337efef8179c",
+        "1460021597acae191216be75c33a78e92d586a3da418834d7a8fa2cfc25559d6",
+        "0881142114947438cefabaef7ac263b7e03a9e9639278853e887f11f628edb52",
+    ],
+    [
+        "ae68c58b0bee59809e519fd1c7fe9a17a8e5dc8ad0afd53b1c883e1a99645084",
+        "5be29db22f14419611d240e54e424922ee4cc606eb590efdc0f2bbaaa165bee0",
+        "ef04cce6ad446576f9c5691f4dd189d5b323d83909138d032ad3a0fb495223b6",
+    ],
+];
+
+pub const HASH_MLDSA44_SIGNATURE_HASHES: [&str; 6] = [
+    "3a274789c53114e1db1c1a47e36283682ecca5b82ed064efe04000b1b978d1ed",
+    "e8bae0fa381739825c36b50b2f966e0562f8ece373f042f2e00914ab5e9527ed",
+    "7c8e79c041ee5de06e4d7e2595657ce153991533e259522bcd06a7be21a1d2d2",
+    "195c481233fed9ed477f198a1efaf09e9f5ed475a542c9431e1010f8065da49a",
+    "994c77bccc2a6c34241bb39ea7333ee371ed834ff6bf410351fe6a6016ece781",
+    "125532eb127aa5764ca63225b5cc2577e32e88534e594d8abefc2ab710a978fe",
+];
+
+pub const MLDSA65_HASHES: [[&str; 3]; 6] = [
+    [
+        "1b0563e3cd3346149c8c9ebcf23b0a4e5a900eea9c6562790a7c63e38663daa2",
+        "fe87058d582b89a47bb18b7ad9fd2636e2c18af0390c7ad6101aa6f78b1ee94e",
+        "a23554d0bd2548dfdec70744db205953ceb8b607df60426ca957cdf3c67ad986",
+    ],
+    [
+        "de0934e7f9d18dbc14728672dedab81ada2fa74b8062133cc6de8381437d4517",
+        "41e1ca65610273cba1ce347700b5800a169a940b34e717940183458af939e8ca",
+        "4a3e0b1ff78d1c44b2a17e89ae2eacbf8c34e96fcf6409e47d02da863df96a58",
+    ],
+    [
+        "6d4d5959f20d2086371ee78b379f012d083f24817ca6564a609a9c036ee9fc6a",
+        "f45b3c05e97177ce573be02ceaa11b7cd143c93fcf36c7882a51a8e7e3441c75",
+        "1e6c082f2c80c9286f96ab5ecbbe45e31019b3ef37d1acac8acf0e4bd0ea87e0",
+    ],
+    [
+        "d7b9467ec48997f583e486f62222e496f7277a16a00c30204ebbaae046046833",
+        "74dfe66a4017ce79c40795d6ef76266e3599076d6a0ce04cf0d0eaf956f4bfa6",
+        "bdd7acfa46e6b71bae50f24aef9215d93b13a78de91f0e3763f2682731060ecc",
+    ],
+    [
+        "96104ae3ffd387c5cc0ba93e5c0cdbfcd15c7f298ee79b05a3461fd3b4b068cc",
+        "2b2f6b748381cbdb80fd0b8db051c60781e22d9e406109d7ba5d80aa24047dff",
+        "5c3e61cce2a1a7bd3a671ed0a2ed3f505183a60b9547511d96d326db44ae50d9",
+    ],
+    [
+        "bb1247b6ee7cb22cf5c602ff824699f392ecb0ac5850fd6363d8a888fde1f533",
+        "224c1dedd9fbc41af168bf5aa5c16d32ed7059aaddf5d68b94413df322bf5ec1",
+        "571a793f607e6941648201978840cbba7ce595c6baf0a8ff37218748ef2b4684",
+    ],
+];
+
+pub const HASH_MLDSA65_SIGNATURE_HASHES: [&str; 6] = [
+    "f7301ec4fb3a511b22ac648b58eafe51faad512b8a871694c98e2973c6716397",
+    "586629402e2489ff1cb11bfd3f6a64f19df554526d34f95b3b37df6188643cb7",
+    "f2ff1726da0d7f1a9cadd5f4107fad3e561406a7677020439c2246af66d40950",
+    "f66ae7b49991bbb7c71b6460ed9b965220ba4bab88892c73e457d4d5e1a239db",
+    "eeeacb53d9f3c3b32eaeb6de70a9888f87c2cffa2a45860a5fed88d41fb98cef",
+    "d4049260484b13d7c711efbc96e860ad96412bd1370f68a785606ed84a135820",
+];
+
+pub const MLDSA87_HASHES: [[&str; 3]; 6] = [
+    [
+        "89886750b57c24db3fc012e61ede59753337374fa7124991549af243496d0637",
+        "c06933a362a7d0da2279a3d70edb9acc560703d534f5722f54e2c8c418311d42",
+        "4ed1792cd4c3cb45cbcf608e81cca2e2384d39a36b4ab2660df3768fe37b3620",
+    ],
+    [
+        "28fc0dcdb592404225b94781d0fa7e6bb0c6967c0f8caf286427bd640a2ec946",
+        "c899deb3917e290d586e2572e541638b715387374a78dd114c4dc4a5c4c6203e",
+        "77dc6b8058bbdd917bc59791b83b3fc754c5f8c02c990373c20a3f865398ac66",
+    ],
+    [
+        "85a60fd0a8d04b09d84238bfc60c2292825cf3b87f74eabc02f21c0c3c31aaa4",
+        "489a05295daaab5baf683f16e63df7a24dc74b102d052f27a9d376c40031a064",
+        "bd75bb6fa3795ddc6115b6287469e46d1f2ef7411b5da53ca7d45d5f0522ae6b",
+    ],
+    [
+        "3c612e0d5a0bcf0965ce65b9fa0bcdf80a94031acbf5e0cca838c08d72e5ef54",
+        "04330ca5256f02fc63e7738099d3070fdd6545014e71eecff42e33094645c222",
+        "6f0038faada1c47542c8bc0e56d0243a7ca7ce5e1703e5a58b11fa8bfe6fec94",
+    ],
+    [
+        "dfe4ab8c76a3a160a23530f6891229355e8479ea3140d94621920bdc7403787b",
+        "050b24a387d4fe8ea7a63da46e4687db8e6205c169ba8e9db6c467fca218cccb",
+        "fdec0114a174381db95e6c8db6c3369d20429104ed3569f6ff7c73966d7ba249",
+    ],
+    [
+        "2ba6fd022074643d6ab76670583636a75203ca71a91a727d587b0fe39f988d73",
+        "7e44e88668b4dfc3987129a19eb98f2cd3c936e492b3fad564d20cc8bae5acdb",
+        "e969d7c3ef243e86f9446f3b7bc98a8fde97db8c2510bfacb6c4ca1f46dd4c06",
+    ],
+];
+
+pub const HASH_MLDSA87_SIGNATURE_HASHES: [&str; 6] = [
+    "7f80e88a90ae3599f33d75027aa976ee520e6c0aa034c859ec2d1b387682b0e2",
+    "306feb543d129a8bf81f27d81d254167226f99161631e346c664b42780aa7820",
+    "f9567d447bcff7e8a60dd5ac2f6bf83b181f4e19f0c3398953c7a9e25dfe0447",
+    "e39e1819740c04967dfe4e0268ee6f2c7a96e5b8e2c72a2357364561f76f1786",
+    "902b1fc6996d09c361147daf85dbd9f3d8af27d3497154025d5e46c259e24d20",
+    "3b92fa870a13c1164d7951ff9cc52b5b0e398b1cc2a41252e108fcc96d62fb9e",
+];
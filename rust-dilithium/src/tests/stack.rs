@@ -0,0 +1,255 @@
+//! Peak stack usage of the core operations
+//!
+//! The operations run on a thread with a large stack. Before each of them, a region of the stack
+//! below the current frame is painted with a pattern, then the operation runs from the same frame,
+//! and the deepest byte that no longer holds the pattern gives the stack that it consumed. The
+//! usage depends on the optimizations, so the budgets apply to release builds, and these tests are
+//! ignored by default and run with:
+//!
+//! `cargo test --release stack -- --ignored --nocapture`
+
+use std::{
+    hint::black_box,
+    ptr::{read_volatile, write_volatile},
+    thread,
+};
+
+use crate::{
+    counter::{Counter, ShakeCounter},
+    expand, low_memory, make_keys, mldsa, sign, verify,
+    workspace::{self, Workspace},
+    Dilithium2, Dilithium3, Dilithium5, MlDsa44, MlDsa65, MlDsa87, ParameterSet, RANDOMNESS_SIZE,
+    SEED_SIZE,
+};
+
+/// Size of the painted region, which must exceed the usage of every operation
+const PAINTED_SIZE: usize = 1 << 20;
+/// Size of the stack of the measurement threads, which leaves room for the harness itself
+const THREAD_STACK_SIZE: usize = 2 * PAINTED_SIZE;
+const PATTERN: u8 = 0xa5;
+
+/// Stack of the main task of the ESP32 firmware, which runs the functions at the root of the
+/// crate, as set by `CONFIG_ESP_MAIN_TASK_STACK_SIZE` in `rust-dilithium-esp/sdkconfig.defaults`
+const ESP_MAIN_TASK_STACK_SIZE: usize = 310000;
+/// Stack targeted by the low-memory and workspace functions, for tasks on targets smaller than
+/// the ESP32-C3
+const SMALL_TASK_STACK_SIZE: usize = 32 << 10;
+
+/// Budgets in bytes, which hold with every parameter set since they are the stacks of the tasks
+/// that run the operations
+const BUDGETS: [(&str, usize); 21] = [
+    ("expand_a", ESP_MAIN_TASK_STACK_SIZE),
+    ("expand_s", ESP_MAIN_TASK_STACK_SIZE),
+    ("expand_y", ESP_MAIN_TASK_STACK_SIZE),
+    ("make_keys", ESP_MAIN_TASK_STACK_SIZE),
+    ("sign", ESP_MAIN_TASK_STACK_SIZE),
+    ("verify", ESP_MAIN_TASK_STACK_SIZE),
+    ("low_memory::make_keys", SMALL_TASK_STACK_SIZE),
+    ("low_memory::sign", SMALL_TASK_STACK_SIZE),
+    ("low_memory::verify", SMALL_TASK_STACK_SIZE),
+    ("workspace::make_keys", SMALL_TASK_STACK_SIZE),
+    ("workspace::sign", SMALL_TASK_STACK_SIZE),
+    ("workspace::verify", SMALL_TASK_STACK_SIZE),
+    ("mldsa::make_keys", ESP_MAIN_TASK_STACK_SIZE),
+    ("mldsa::sign", ESP_MAIN_TASK_STACK_SIZE),
+    ("mldsa::verify", ESP_MAIN_TASK_STACK_SIZE),
+    ("mldsa::make_keys_low_memory", SMALL_TASK_STACK_SIZE),
+    ("mldsa::sign_low_memory", SMALL_TASK_STACK_SIZE),
+    ("mldsa::verify_low_memory", SMALL_TASK_STACK_SIZE),
+    ("mldsa::make_keys_with_workspace", SMALL_TASK_STACK_SIZE),
+    ("mldsa::sign_with_workspace", SMALL_TASK_STACK_SIZE),
+    ("mldsa::verify_with_workspace", SMALL_TASK_STACK_SIZE),
+];
+
+/// Paints the region of the stack below the frame of the caller, and returns its lowest address
+#[inline(never)]
+fn paint() -> usize {
+    let mut painted = [0u8; PAINTED_SIZE];
+
+    for byte in painted.iter_mut() {
+        // Volatile writes so that the painting is not optimized away
+        unsafe { write_volatile(byte, PATTERN) };
+    }
+
+    black_box(&painted).as_ptr() as usize
+}
+
+/// Returns the number of bytes of stack consumed by `f`, called from the same frame as [`paint`]
+#[inline(never)]
+fn measure(f: &mut dyn FnMut()) -> usize {
+    let bottom = paint();
+    black_box(f)();
+
+    // The painted region is no longer in use, but still belongs to the stack of the thread
+    let untouched = (0..PAINTED_SIZE)
+        .take_while(|&i| unsafe { read_volatile((bottom + i) as *const u8) } == PATTERN)
+        .count();
+
+    assert!(untouched > 0, "the painted region is too small");
+
+    PAINTED_SIZE - untouched
+}
+
+/// Measures the operations of [`BUDGETS`] on a dedicated thread, so that they all start from the
+/// same frame, with `P` for round-3 Dilithium and `M` for ML-DSA
+fn measure_parameter_sets<P: ParameterSet + 'static, M: ParameterSet + 'static>(
+) -> [usize; BUDGETS.len()] {
+    thread::Builder::new()
+        .stack_size(THREAD_STACK_SIZE)
+        .spawn(measure_operations::<P, M>)
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+fn measure_operations<P: ParameterSet, M: ParameterSet>() -> [usize; BUDGETS.len()] {
+    let seed = [0x2a; SEED_SIZE / 2];
+    let rnd = [0x2a; RANDOMNESS_SIZE];
+    let (pk, sk) = make_keys::<P, ShakeCounter>(&seed).unwrap();
+    let signature = sign::<P, ShakeCounter>(b"message", &sk).unwrap();
+    let mut ws = Box::<Workspace<P>>::default();
+    let (mldsa_pk, mldsa_sk) = mldsa::make_keys::<M>(&seed).unwrap();
+    let mldsa_signature = mldsa::sign::<M>(b"message", b"", &mldsa_sk, &rnd).unwrap();
+    let mut mldsa_ws = Box::<Workspace<M>>::default();
+
+    [
+        measure(&mut || {
+            black_box(expand::expand_a::<P>(ShakeCounter::new(&seed)));
+        }),
+        measure(&mut || {
+            black_box(expand::expand_s::<P, P::L>(
+                ShakeCounter::new_wide(&[0; SEED_SIZE]),
+                0,
+            ));
+        }),
+        measure(&mut || {
+            black_box(expand::expand_y::<P>(
+                ShakeCounter::new_wide(&[0; SEED_SIZE]),
+                0,
+            ));
+        }),
+        measure(&mut || {
+            black_box(make_keys::<P, ShakeCounter>(&seed).unwrap());
+        }),
+        measure(&mut || {
+            black_box(sign::<P, ShakeCounter>(b"message", &sk).unwrap());
+        }),
+        measure(&mut || {
+            black_box(verify::<P, ShakeCounter>(b"message", &signature, &pk)).unwrap();
+        }),
+        measure(&mut || {
+            black_box(low_memory::make_keys::<P, ShakeCounter>(&seed).unwrap());
+        }),
+        measure(&mut || {
+            black_box(low_memory::sign::<P, ShakeCounter>(b"message", &sk).unwrap());
+        }),
+        measure(&mut || {
+            black_box(low_memory::verify::<P, ShakeCounter>(
+                b"message", &signature, &pk,
+            ))
+            .unwrap();
+        }),
+        measure(&mut || {
+            black_box(workspace::make_keys::<P, ShakeCounter>(&seed, &mut ws).unwrap());
+        }),
+        measure(&mut || {
+            black_box(workspace::sign::<P, ShakeCounter>(b"message", &sk, &mut ws).unwrap());
+        }),
+        measure(&mut || {
+            black_box(workspace::verify::<P, ShakeCounter>(
+                b"message", &signature, &pk, &mut ws,
+            ))
+            .unwrap();
+        }),
+        measure(&mut || {
+            black_box(mldsa::make_keys::<M>(&seed).unwrap());
+        }),
+        measure(&mut || {
+            black_box(mldsa::sign::<M>(b"message", b"", &mldsa_sk, &rnd).unwrap());
+        }),
+        measure(&mut || {
+            black_box(mldsa::verify::<M>(
+                b"message",
+                b"",
+                &mldsa_signature,
+                &mldsa_pk,
+            ))
+            .unwrap();
+        }),
+        measure(&mut || {
+            black_box(mldsa::make_keys_low_memory::<M>(&seed).unwrap());
+        }),
+        measure(&mut || {
+            black_box(mldsa::sign_low_memory::<M>(b"message", b"", &mldsa_sk, &rnd).unwrap());
+        }),
+        measure(&mut || {
+            black_box(mldsa::verify_low_memory::<M>(
+                b"message",
+                b"",
+                &mldsa_signature,
+                &mldsa_pk,
+            ))
+            .unwrap();
+        }),
+        measure(&mut || {
+            black_box(mldsa::make_keys_with_workspace::<M>(&seed, &mut mldsa_ws).unwrap());
+        }),
+        measure(&mut || {
+            black_box(
+                mldsa::sign_with_workspace::<M>(b"message", b"", &mldsa_sk, &rnd, &mut mldsa_ws)
+                    .unwrap(),
+            );
+        }),
+        measure(&mut || {
+            black_box(mldsa::verify_with_workspace::<M>(
+                b"message",
+                b"",
+                &mldsa_signature,
+                &mldsa_pk,
+                &mut mldsa_ws,
+            ))
+            .unwrap();
+        }),
+    ]
+}
+
+#[test]
+#[ignore]
+fn stack_usage() {
+    let sdkconfig = include_str!("../../../rust-dilithium-esp/sdkconfig.defaults");
+    let main_task_stack_size =
+        format!("CONFIG_ESP_MAIN_TASK_STACK_SIZE={ESP_MAIN_TASK_STACK_SIZE}");
+    assert!(
+        sdkconfig.lines().any(|line| line == main_task_stack_size),
+        "the budget of the main task does not match sdkconfig.defaults"
+    );
+
+    let usages = [
+        measure_parameter_sets::<Dilithium2, MlDsa44>(),
+        measure_parameter_sets::<Dilithium3, MlDsa65>(),
+        measure_parameter_sets::<Dilithium5, MlDsa87>(),
+    ];
+    let mut exceeded = Vec::new();
+
+    println!(
+        "{:<32} {:>10} {:>18} {:>18} {:>18}",
+        "usage", "budget", "Dilithium2/MlDsa44", "Dilithium3/MlDsa65", "Dilithium5/MlDsa87"
+    );
+
+    for (i, (operation, budget)) in BUDGETS.iter().enumerate() {
+        print!("{operation:<32} {budget:>10}");
+
+        for parameter_set_usages in &usages {
+            let usage = parameter_set_usages[i];
+            print!(" {usage:>18}");
+
+            if usage > *budget {
+                exceeded.push(*operation);
+            }
+        }
+
+        println!();
+    }
+
+    assert!(exceeded.is_empty(), "budget exceeded: {exceeded:?}");
+}
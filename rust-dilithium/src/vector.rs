@@ -1,181 +1,289 @@
 use core::{
-    array::from_fn,
+    fmt::{self, Debug},
     iter::zip,
-    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+    ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
     slice,
 };
 
+use zeroize::Zeroize;
+
 use crate::{
     array_math::{pointwise_add_inplace, pointwise_sub_inplace, scalar_mul},
     coefficient::{self, Coefficient},
     packing::Pack,
+    params::{Dim, Dimension, ParameterSet},
     polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial, NB_COEFFICIENTS},
-    TryCollectArray, D,
+    D, Q,
 };
 
-pub type Matrix<Scalar, const N: usize, const M: usize> = Vector<Vector<Scalar, N>, M>;
+pub type Matrix<Scalar, N, M> = Vector<Vector<Scalar, N>, M>;
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Vector<Scalar, const N: usize> {
-    coefficients: [Scalar; N],
+pub struct Vector<Scalar, N: Dimension> {
+    coefficients: N::Array<Scalar>,
 }
 
-impl<const N: usize> Vector<NTTPolynomial, N> {
+impl<N: Dimension> Vector<NTTPolynomial, N> {
     pub fn into_plain(self) -> Vector<PlainPolynomial, N> {
-        Vector::from(
-            self.coefficients
-                .into_iter()
-                .map(|poly| poly.into_plain())
-                .try_collect_array()
-                .unwrap(),
-        )
+        self.coefficients
+            .into_iter()
+            .map(|poly| poly.into_plain())
+            .try_collect_vector()
+            .unwrap()
+    }
+
+    /// Writes the inverse NTT of the vector to `dst`, leaving the vector as is
+    pub fn plain_into(&self, dst: &mut Vector<PlainPolynomial, N>) {
+        for (coeff, dst_coeff) in zip(self, dst) {
+            coeff.plain_into(dst_coeff);
+        }
     }
 
     pub fn reduce_32(mut self) -> Self {
-        self.map_inplace(&mut coefficient::reduce_32);
+        self.reduce_32_inplace();
         self
     }
 
+    pub fn reduce_32_inplace(&mut self) {
+        self.map_inplace(&mut coefficient::reduce_32);
+    }
+
     fn map_inplace(&mut self, f: &mut impl FnMut(Coefficient) -> Coefficient) {
-        for coeff in self.coefficients.iter_mut() {
+        for coeff in self.coefficients.as_mut() {
             coeff.map_inplace(f);
         }
     }
 }
 
-impl<const N: usize> Vector<PlainPolynomial, N> {
+impl<N: Dimension> Vector<PlainPolynomial, N> {
     pub fn into_ntt(self) -> Vector<NTTPolynomial, N> {
-        Vector::from(
-            self.coefficients
-                .into_iter()
-                .map(|poly| poly.into_ntt())
-                .try_collect_array()
-                .unwrap(),
-        )
+        self.coefficients
+            .into_iter()
+            .map(|poly| poly.into_ntt())
+            .try_collect_vector()
+            .unwrap()
+    }
+
+    /// Writes the NTT of the vector to `dst`, leaving the vector as is
+    pub fn ntt_into(&self, dst: &mut Vector<NTTPolynomial, N>) {
+        for (coeff, dst_coeff) in zip(self, dst) {
+            coeff.ntt_into(dst_coeff);
+        }
     }
 
     pub fn reduce_32(mut self) -> Self {
-        self.map_inplace(&mut coefficient::reduce_32);
+        self.reduce_32_inplace();
         self
     }
 
+    pub fn reduce_32_inplace(&mut self) {
+        self.map_inplace(&mut coefficient::reduce_32);
+    }
+
     pub fn caddq(mut self) -> Self {
-        self.map_inplace(&mut coefficient::caddq);
+        self.caddq_inplace();
         self
     }
 
-    pub fn decompose(mut self) -> (Self, Self) {
-        let other = self.map_fork(&mut coefficient::decompose);
+    pub fn caddq_inplace(&mut self) {
+        self.map_inplace(&mut coefficient::caddq);
+    }
+
+    pub fn decompose<P: ParameterSet>(mut self) -> (Self, Self) {
+        let mut other = Self::default();
+        self.decompose_into::<P>(&mut other);
         (self, other)
     }
 
+    /// Keeps the low bits of the coefficients and writes the high bits to `high`
+    pub fn decompose_into<P: ParameterSet>(&mut self, high: &mut Self) {
+        self.map_fork_into(high, &mut coefficient::decompose::<P>);
+    }
+
     pub fn power2round(mut self) -> (Self, Self) {
-        let other = self.map_fork(&mut coefficient::power2round);
+        let mut other = Self::default();
+        self.power2round_into(&mut other);
         (self, other)
     }
 
+    /// Keeps the low bits of the coefficients and writes the high bits to `high`
+    pub fn power2round_into(&mut self, high: &mut Self) {
+        self.map_fork_into(high, &mut coefficient::power2round);
+    }
+
     pub fn max(&self) -> Coefficient {
         self.coefficients
+            .as_ref()
             .iter()
             .map(|coeff| coeff.max())
-            .max()
-            .unwrap()
+            .fold(0, coefficient::max)
     }
 
-    pub fn use_hint(mut self, hint: &[[bool; NB_COEFFICIENTS]; N]) -> Self {
-        let mut hint_it = hint.iter().flatten();
+    pub fn use_hint<P: ParameterSet>(mut self, hint: &N::Array<[bool; NB_COEFFICIENTS]>) -> Self {
+        self.use_hint_inplace::<P>(hint);
+        self
+    }
+
+    pub fn use_hint_inplace<P: ParameterSet>(&mut self, hint: &N::Array<[bool; NB_COEFFICIENTS]>) {
+        let mut hint_it = hint.as_ref().iter().flatten();
         self.map_inplace(&mut |coeff| {
             let &hint = hint_it.next().unwrap();
-            let (a0, a1) = coefficient::decompose(coeff);
+            let (a0, a1) = coefficient::decompose::<P>(coeff);
 
-            if hint {
+            if !hint {
+                a1
+            } else if P::GAMMA2 == (Q - 1) / 32 {
                 (a1 + (if a0 > 0 { 1 } else { -1 })) & 15
+            } else if a0 > 0 {
+                if a1 == 43 {
+                    0
+                } else {
+                    a1 + 1
+                }
+            } else if a1 == 0 {
+                43
             } else {
-                a1
+                a1 - 1
             }
         });
+    }
 
+    pub fn shift_d(mut self) -> Self {
+        self.shift_d_inplace();
         self
     }
 
-    pub fn shift_d(mut self) -> Self {
+    pub fn shift_d_inplace(&mut self) {
         self.map_inplace(&mut |coeff| coeff << D);
-        self
     }
 
     fn map_inplace(&mut self, f: &mut impl FnMut(Coefficient) -> Coefficient) {
-        for coeff in self.coefficients.iter_mut() {
+        for coeff in self.coefficients.as_mut() {
             coeff.map_inplace(f);
         }
     }
 
-    fn map_fork(&mut self, f: &mut impl FnMut(Coefficient) -> (Coefficient, Coefficient)) -> Self {
-        let retval_it = self.coefficients.iter_mut().map(|coeff| coeff.map_fork(f));
-        Self::from(retval_it.try_collect_array().unwrap())
+    fn map_fork_into(
+        &mut self,
+        other: &mut Self,
+        f: &mut impl FnMut(Coefficient) -> (Coefficient, Coefficient),
+    ) {
+        for (coeff, other_coeff) in zip(&mut *self, other) {
+            coeff.map_fork_into(other_coeff, f);
+        }
     }
 
-    pub fn dump(&self) -> [[i64; 256]; N] {
-        let mut it = self.coefficients.iter().map(|coeff| coeff.dump());
-        from_fn(|_| it.next().unwrap())
+    pub fn dump(&self) -> N::Array<[i64; 256]> {
+        let mut it = self.coefficients.as_ref().iter().map(|coeff| coeff.dump());
+        N::try_from_fn(|_| it.next()).unwrap()
     }
 }
 
-impl<Scalar: AddAssign, const N: usize> Add for Vector<Scalar, N> {
+impl<Scalar: AddAssign, N: Dimension> Add for Vector<Scalar, N> {
     type Output = Self;
 
     fn add(mut self, rhs: Self) -> Self {
-        pointwise_add_inplace(&mut self.coefficients, rhs.coefficients);
+        pointwise_add_inplace(self.coefficients.as_mut(), rhs.coefficients);
         self
     }
 }
 
-impl<Scalar: SubAssign, const N: usize> Sub for Vector<Scalar, N> {
+impl<'a, Scalar: AddAssign<&'a Scalar> + 'a, N: Dimension> AddAssign<&'a Self>
+    for Vector<Scalar, N>
+{
+    fn add_assign(&mut self, rhs: &'a Self) {
+        pointwise_add_inplace(self.coefficients.as_mut(), rhs);
+    }
+}
+
+impl<Scalar: SubAssign, N: Dimension> Sub for Vector<Scalar, N> {
     type Output = Self;
 
     fn sub(mut self, rhs: Self) -> Self {
-        pointwise_sub_inplace(&mut self.coefficients, rhs.coefficients);
+        pointwise_sub_inplace(self.coefficients.as_mut(), rhs.coefficients);
         self
     }
 }
 
-impl<const N: usize> Mul for &Vector<NTTPolynomial, N> {
+impl<'a, Scalar: SubAssign<&'a Scalar> + 'a, N: Dimension> SubAssign<&'a Self>
+    for Vector<Scalar, N>
+{
+    fn sub_assign(&mut self, rhs: &'a Self) {
+        pointwise_sub_inplace(self.coefficients.as_mut(), rhs);
+    }
+}
+
+impl<N: Dimension> Mul for &Vector<NTTPolynomial, N> {
     type Output = NTTPolynomial;
 
     fn mul(self, rhs: Self) -> NTTPolynomial {
-        scalar_mul(&self.coefficients, &rhs.coefficients)
+        scalar_mul(self.coefficients.as_ref(), rhs.coefficients.as_ref())
     }
 }
 
-impl<const N: usize> Mul<&NTTPolynomial> for Vector<NTTPolynomial, N> {
+impl<N: Dimension> Mul<&NTTPolynomial> for Vector<NTTPolynomial, N> {
     type Output = Vector<NTTPolynomial, N>;
 
     fn mul(mut self, rhs: &NTTPolynomial) -> Self::Output {
-        for coeff in self.coefficients.iter_mut() {
+        self *= rhs;
+        self
+    }
+}
+
+impl<N: Dimension> MulAssign<&NTTPolynomial> for Vector<NTTPolynomial, N> {
+    fn mul_assign(&mut self, rhs: &NTTPolynomial) {
+        for coeff in self.coefficients.as_mut() {
             *coeff *= rhs;
         }
-
-        self
     }
 }
 
-impl<const N: usize, const M: usize> Mul<&Vector<NTTPolynomial, M>>
-    for &Matrix<NTTPolynomial, M, N>
-{
+impl<N: Dimension, M: Dimension> Mul<&Vector<NTTPolynomial, M>> for &Matrix<NTTPolynomial, M, N> {
     type Output = Vector<NTTPolynomial, N>;
 
     fn mul(self, rhs: &Vector<NTTPolynomial, M>) -> Self::Output {
-        let retval_it = self.coefficients.iter().map(|coeff| coeff * rhs);
-        From::from(retval_it.try_collect_array().unwrap())
+        let retval_it = self.coefficients.as_ref().iter().map(|coeff| coeff * rhs);
+        retval_it.try_collect_vector().unwrap()
     }
 }
 
-impl<T, const N: usize> From<[T; N]> for Vector<T, N> {
+impl<N: Dimension, M: Dimension> Matrix<NTTPolynomial, M, N> {
+    /// Writes the product of the matrix and `rhs` to `dst`, as `self * rhs` does without the
+    /// temporaries
+    pub fn mul_into(&self, rhs: &Vector<NTTPolynomial, M>, dst: &mut Vector<NTTPolynomial, N>) {
+        for (row, dst_coeff) in zip(self, dst) {
+            dst_coeff.zeroize();
+
+            for (lhs_coeff, rhs_coeff) in zip(row, rhs) {
+                dst_coeff.add_product_assign(lhs_coeff, rhs_coeff);
+            }
+        }
+    }
+}
+
+impl<T> Vector<T, Dim<1>> {
+    pub fn into_polynomial(self) -> T {
+        let [retval] = self.coefficients;
+        retval
+    }
+
+    pub fn as_polynomial(&self) -> &T {
+        let [retval] = &self.coefficients;
+        retval
+    }
+
+    pub fn as_polynomial_mut(&mut self) -> &mut T {
+        let [retval] = &mut self.coefficients;
+        retval
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T, Dim<N>> {
     fn from(coefficients: [T; N]) -> Self {
         Self { coefficients }
     }
 }
 
-impl<T: Pack, const N: usize> Pack for Vector<T, N> {
+impl<T: Pack, N: Dimension> Pack for Vector<T, N> {
     type Input = <T as Pack>::Input;
     type Output = <T as Pack>::Output;
 
@@ -184,8 +292,8 @@ impl<T: Pack, const N: usize> Pack for Vector<T, N> {
         f: &impl Fn(&[Self::Input; IN_N]) -> [Self::Output; OUT_N],
         output: &mut [Self::Output],
     ) {
-        let coeff_it = self.coefficients.iter();
-        let output_chunk_it = output.chunks_exact_mut(output.len() / self.coefficients.len());
+        let coeff_it = self.coefficients.as_ref().iter();
+        let output_chunk_it = output.chunks_exact_mut(output.len() / N::N);
 
         for (coeff, output_chunk) in zip(coeff_it, output_chunk_it) {
             coeff.pack_inplace(f, output_chunk);
@@ -197,27 +305,86 @@ impl<T: Pack, const N: usize> Pack for Vector<T, N> {
         f: &impl Fn(&[Self::Output; OUT_N]) -> [Self::Input; IN_N],
     ) -> Self {
         let it = packed
-            .chunks_exact(packed.len() / N)
+            .chunks_exact(packed.len() / N::N)
             .map(|chunk| T::unpack(chunk, f));
 
-        Self::from(it.try_collect_array().unwrap())
+        it.try_collect_vector().unwrap()
     }
 }
 
-impl<T, const N: usize> Default for Vector<T, N>
-where
-    T: Default,
-{
+impl<T: Default, N: Dimension> Default for Vector<T, N> {
     fn default() -> Self {
-        Self::from(from_fn(|_| T::default()))
+        Self {
+            coefficients: N::try_from_fn(|_| Some(T::default())).unwrap(),
+        }
     }
 }
 
-impl<'a, T, const N: usize> IntoIterator for &'a Vector<T, N> {
+impl<T: Clone, N: Dimension> Clone for Vector<T, N> {
+    fn clone(&self) -> Self {
+        self.into_iter().cloned().try_collect_vector().unwrap()
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        for (coeff, source_coeff) in zip(self, source) {
+            coeff.clone_from(source_coeff);
+        }
+    }
+}
+
+impl<T: Zeroize, N: Dimension> Zeroize for Vector<T, N> {
+    fn zeroize(&mut self) {
+        for coeff in self {
+            coeff.zeroize();
+        }
+    }
+}
+
+impl<T: PartialEq, N: Dimension> PartialEq for Vector<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coefficients.as_ref() == other.coefficients.as_ref()
+    }
+}
+
+impl<T: Eq, N: Dimension> Eq for Vector<T, N> {}
+
+impl<T: Debug, N: Dimension> Debug for Vector<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vector")
+            .field("coefficients", &self.coefficients.as_ref())
+            .finish()
+    }
+}
+
+impl<'a, T, N: Dimension> IntoIterator for &'a Vector<T, N> {
     type Item = &'a T;
     type IntoIter = slice::Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.coefficients.iter()
+        self.coefficients.as_ref().iter()
+    }
+}
+
+impl<'a, T, N: Dimension> IntoIterator for &'a mut Vector<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.coefficients.as_mut().iter_mut()
+    }
+}
+
+pub trait TryCollectVector<T> {
+    fn try_collect_vector<N: Dimension>(self) -> Option<Vector<T, N>>;
+}
+
+impl<T, Iter> TryCollectVector<T> for Iter
+where
+    Iter: Iterator<Item = T>,
+{
+    fn try_collect_vector<N: Dimension>(mut self) -> Option<Vector<T, N>> {
+        Some(Vector {
+            coefficients: N::try_from_fn(|_| self.next())?,
+        })
     }
 }
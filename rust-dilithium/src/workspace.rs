@@ -0,0 +1,448 @@
+//! Key generation, signing and verification in a workspace provided by the caller
+//!
+//! The functions at the root of the crate keep their intermediate vectors and matrices on the
+//! stack, and clone the secret vectors at each signing attempt to multiply them with the
+//! challenge. The functions of this module keep all of them in a [`Workspace`] instead, and
+//! compute in place, so that only single polynomials, hash states and the output are left on the
+//! stack. They yield the same keys, signatures and verification results as the functions at the
+//! root of the crate.
+//!
+//! A workspace can be made in a `static` with [`Workspace::new`], or initialized in memory
+//! allocated by the caller, such as a heap box, with [`Workspace::init`]. The secret values left
+//! in the workspace are wiped at the end of key generation and signing.
+
+use core::{
+    iter::zip,
+    mem::{self, MaybeUninit},
+};
+
+use sha3::{
+    digest::{ExtendableOutput, ExtendableOutputReset, Update, XofReader},
+    Shake256,
+};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    counter::Counter,
+    expand::{expand_a_into, expand_s_into, expand_y_into},
+    hash_message, make_challenge, make_hint_into, make_mu, make_private_key, make_rho_prime,
+    make_signature, make_tr, pack_public_key,
+    packing::Pack,
+    params::Dimension,
+    polynomial::{ntt::NTTPolynomial, plain::PlainPolynomial},
+    public_key_partition_sizes, secret_key_partition_sizes, signature_partition_sizes, t0_unpacker,
+    t1_unpacker, unpack_eta, unpack_hint_into, unpack_z_into, update_with_w1,
+    vector::{Matrix, Vector},
+    Error, Hint, Mu, ParameterSet, Partition, PublicKey, SecretKey, Seed, Signature,
+    CHALLENGE_SEED_SIZE_MAX, RANDOMNESS_SIZE, SEED_SIZE, T0_PACKED_SIZE, T1_PACKED_SIZE,
+};
+
+/// Memory for the intermediate values of key generation, signing and verification
+///
+/// Its size depends on the parameter set only, and is given by `size_of::<Workspace<P>>()`.
+pub struct Workspace<P: ParameterSet> {
+    a: Matrix<NTTPolynomial, P::L, P::K>,
+    s1_hat: Vector<NTTPolynomial, P::L>,
+    s2_hat: Vector<NTTPolynomial, P::K>,
+    /// `t0` when signing, `t1 * 2^d` when verifying
+    t_hat: Vector<NTTPolynomial, P::K>,
+    /// `y` when signing, `s1` when making keys
+    y: Vector<PlainPolynomial, P::L>,
+    z: Vector<PlainPolynomial, P::L>,
+    l_product: Vector<NTTPolynomial, P::L>,
+    k_product: Vector<NTTPolynomial, P::K>,
+    /// `w0` when signing, `t` then `t0` when making keys, `t1` when verifying
+    w0: Vector<PlainPolynomial, P::K>,
+    /// `w1` when signing and verifying, `t1` when making keys
+    w1: Vector<PlainPolynomial, P::K>,
+    /// `cs2` then `ct` when signing, `s2` when making keys
+    k_plain: Vector<PlainPolynomial, P::K>,
+    challenge: NTTPolynomial,
+    hint: Hint<P>,
+}
+
+impl<P: ParameterSet> Workspace<P> {
+    /// Makes a zeroed workspace, at compile time when used in a `static`
+    pub const fn new() -> Self {
+        // Safe since the workspace only holds arrays of coefficients and of booleans, for which
+        // zero is a valid value
+        unsafe { mem::zeroed() }
+    }
+
+    /// Zeroes the workspace in `slot`, without building it elsewhere first
+    pub fn init(slot: &mut MaybeUninit<Self>) -> &mut Self {
+        // Safe for the same reason as `new`
+        unsafe {
+            slot.as_mut_ptr().write_bytes(0, 1);
+            slot.assume_init_mut()
+        }
+    }
+}
+
+impl<P: ParameterSet> Default for Workspace<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: ParameterSet> Zeroize for Workspace<P> {
+    fn zeroize(&mut self) {
+        self.a.zeroize();
+        self.s1_hat.zeroize();
+        self.s2_hat.zeroize();
+        self.t_hat.zeroize();
+        self.y.zeroize();
+        self.z.zeroize();
+        self.l_product.zeroize();
+        self.k_product.zeroize();
+        self.w0.zeroize();
+        self.w1.zeroize();
+        self.k_plain.zeroize();
+        self.challenge.zeroize();
+
+        for poly in self.hint.as_mut() {
+            poly.zeroize();
+        }
+    }
+}
+
+impl<P: ParameterSet> Drop for Workspace<P> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<P: ParameterSet> ZeroizeOnDrop for Workspace<P> {}
+
+/// Derives the same key pair as [`crate::make_keys`]
+// Inlined so that the keys are not copied once more on their way to the caller
+#[inline]
+pub fn make_keys<P: ParameterSet, Ctr: Counter>(
+    seed: &Seed,
+    ws: &mut Workspace<P>,
+) -> Result<(PublicKey<P>, SecretKey<P>), Error> {
+    let mut hasher = Shake256::default();
+    hasher.update(seed);
+
+    Ok(make_keys_internal::<P, Ctr>(hasher, ws))
+}
+
+/// Signs `msg` in the deterministic variant, see [`crate::sign`]
+pub fn sign<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), &[], sk, ws))
+}
+
+/// Signs `msg` in the hedged variant, where `rnd` must be fresh randomness, see
+/// [`crate::sign_hedged`]
+pub fn sign_hedged<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    let [_, _, tr, ..] = sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    Ok(sign_internal::<P, Ctr>(&hash_message(tr, msg), rnd, sk, ws))
+}
+
+/// Signs a message given by its representative `mu`, see [`crate::sign_mu`]
+pub fn sign_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, &[], sk, ws))
+}
+
+/// Signs a message given by its representative `mu` in the hedged variant, see
+/// [`crate::sign_mu_hedged`]
+pub fn sign_mu_hedged<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    sk: &SecretKey<P>,
+    rnd: &[u8; RANDOMNESS_SIZE],
+    ws: &mut Workspace<P>,
+) -> Result<Signature<P>, Error> {
+    Ok(sign_internal::<P, Ctr>(mu, rnd, sk, ws))
+}
+
+/// Verifies `signature` against `msg`, see [`crate::verify`]
+pub fn verify<P: ParameterSet, Ctr: Counter>(
+    msg: &[u8],
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(&make_mu::<P>(msg, pk), signature, pk, ws)
+}
+
+/// Verifies `signature` against a message given by its representative `mu`, see
+/// [`crate::verify_mu`]
+pub fn verify_mu<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<(), Error> {
+    verify_internal::<P, Ctr>(mu, signature, pk, ws)
+}
+
+/// Derives a key pair from `hasher`, which must have absorbed the seed of the key pair, as
+/// [`crate::make_keys_internal`] does
+pub(crate) fn make_keys_internal<P: ParameterSet, Ctr: Counter>(
+    mut hasher: Shake256,
+    ws: &mut Workspace<P>,
+) -> (PublicKey<P>, SecretKey<P>) {
+    let Workspace {
+        a,
+        y: s1,
+        l_product: s1_hat,
+        k_product: t_hat,
+        w0: t,
+        w1: t1,
+        k_plain: s2,
+        ..
+    } = &mut *ws;
+
+    let mut rho = [0u8; SEED_SIZE / 2];
+    let mut rho_prime = Zeroizing::new([0u8; SEED_SIZE]);
+    let mut key = Zeroizing::new([0u8; SEED_SIZE / 2]);
+
+    let mut reader = hasher.finalize_xof_reset();
+    reader.read(&mut rho);
+    reader.read(rho_prime.as_mut());
+    reader.read(key.as_mut());
+
+    expand_s_into::<P, P::L>(Ctr::new_wide(&rho_prime), 0, s1);
+    expand_s_into::<P, P::K>(Ctr::new_wide(&rho_prime), P::L::N as u16, s2);
+    expand_a_into::<P>(Ctr::new(&rho), a);
+
+    s1.ntt_into(s1_hat);
+    a.mul_into(s1_hat, t_hat);
+    t_hat.reduce_32_inplace();
+    t_hat.plain_into(t);
+    t.caddq_inplace();
+    *t += &*s2;
+    t.power2round_into(t1);
+
+    let pk = pack_public_key::<P>(&rho, t1);
+    let tr = make_tr::<P>(&pk);
+    let sk = make_private_key::<P>(&rho, &tr[..P::TR_SIZE], &key, t, s1, s2);
+
+    ws.zeroize();
+
+    (pk, sk)
+}
+
+/// Signs the message representative `mu` as [`crate::sign_internal`] does
+fn sign_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    rnd: &[u8],
+    sk: &SecretKey<P>,
+    ws: &mut Workspace<P>,
+) -> Signature<P> {
+    let [rho, key, _, packed_s1, packed_s2, packed_t0] =
+        sk.as_bytes().partition(&secret_key_partition_sizes::<P>());
+
+    let unpack_eta_ntt = |packed: &[u8]| unpack_eta::<P, PlainPolynomial>(packed).into_ntt();
+    unpack_ntt_into(
+        packed_s1,
+        P::ETA_PACKED_SIZE,
+        &mut ws.s1_hat,
+        unpack_eta_ntt,
+    );
+    unpack_ntt_into(
+        packed_s2,
+        P::ETA_PACKED_SIZE,
+        &mut ws.s2_hat,
+        unpack_eta_ntt,
+    );
+    unpack_ntt_into(packed_t0, T0_PACKED_SIZE, &mut ws.t_hat, |packed| {
+        PlainPolynomial::unpack(packed, &t0_unpacker).into_ntt()
+    });
+
+    // Unwrapping is safe here because the slice is of the right size
+    expand_a_into::<P>(Ctr::new(rho.try_into().unwrap()), &mut ws.a);
+
+    let rho_prime = make_rho_prime(key, rnd, mu);
+
+    // Will not panic since input iterator is infinite
+    let sample = |nonce| sample_signature::<P, Ctr>(nonce, &rho_prime, mu, ws);
+    let retval = (0..).find_map(sample).unwrap();
+
+    ws.zeroize();
+
+    retval
+}
+
+/// Makes a signing attempt as [`crate::sample_signature`] does, with the products of the
+/// challenge and the secret vectors computed in place
+#[inline(never)]
+fn sample_signature<P: ParameterSet, Ctr: Counter>(
+    nonce: u16,
+    rho_prime: &[u8; SEED_SIZE],
+    mu: &Mu,
+    ws: &mut Workspace<P>,
+) -> Option<Signature<P>> {
+    let Workspace {
+        a,
+        s1_hat,
+        s2_hat,
+        t_hat: t0_hat,
+        y,
+        z,
+        l_product,
+        k_product,
+        w0,
+        w1,
+        k_plain,
+        challenge,
+        hint,
+    } = ws;
+
+    let mut hasher = Shake256::default();
+
+    expand_y_into::<P>(Ctr::new_wide(rho_prime), nonce, y);
+    y.ntt_into(l_product);
+    a.mul_into(l_product, k_product);
+    k_product.reduce_32_inplace();
+    k_product.plain_into(w0);
+    w0.caddq_inplace();
+    w0.decompose_into::<P>(w1);
+
+    let mut challenge_seed_buf = [0u8; CHALLENGE_SEED_SIZE_MAX];
+    let challenge_seed = &mut challenge_seed_buf[..P::CHALLENGE_SEED_SIZE];
+
+    hasher.update(mu);
+    update_with_w1::<P>(&mut hasher, w1);
+
+    let mut reader = hasher.finalize_xof_reset();
+    reader.read(challenge_seed);
+
+    *challenge = make_challenge::<P>(challenge_seed).into_ntt();
+
+    l_product.clone_from(s1_hat);
+    *l_product *= &*challenge;
+    l_product.plain_into(z);
+    *z += &*y;
+    z.reduce_32_inplace();
+    if z.max() >= P::GAMMA1 - P::BETA {
+        return None;
+    }
+
+    // `w0` is replaced by `w0 - cs2`, then by `w0 - cs2 + ct` from which the hint is made
+    k_product.clone_from(s2_hat);
+    *k_product *= &*challenge;
+    k_product.plain_into(k_plain);
+    *w0 -= &*k_plain;
+    w0.reduce_32_inplace();
+    if w0.max() >= P::GAMMA2 - P::BETA {
+        return None;
+    }
+
+    k_product.clone_from(t0_hat);
+    *k_product *= &*challenge;
+    k_product.plain_into(k_plain);
+    k_plain.reduce_32_inplace();
+    if k_plain.max() >= P::GAMMA2 {
+        return None;
+    }
+
+    *w0 += &*k_plain;
+    let hint_bits_count = make_hint_into::<P, P::K>(w0, w1, hint);
+    if hint_bits_count > P::OMEGA {
+        return None;
+    }
+
+    Some(make_signature::<P>(challenge_seed, z, hint))
+}
+
+/// Verifies `signature` against the message representative `mu` as [`crate::verify_internal`]
+/// does
+fn verify_internal<P: ParameterSet, Ctr: Counter>(
+    mu: &Mu,
+    signature: &Signature<P>,
+    pk: &PublicKey<P>,
+    ws: &mut Workspace<P>,
+) -> Result<(), Error> {
+    let Workspace {
+        a,
+        t_hat: shifted_t1_hat,
+        z,
+        l_product: z_hat,
+        k_product,
+        w0: t1,
+        w1,
+        challenge,
+        hint,
+        ..
+    } = ws;
+
+    let [rho, packed_t1] = pk.as_bytes().partition(&public_key_partition_sizes::<P>());
+    let [expected_challenge_seed, packed_z, packed_hint] = signature
+        .as_bytes()
+        .partition(&signature_partition_sizes::<P>());
+
+    // The signature is unpacked first so that malformed signatures are rejected before the
+    // expansion of the matrix
+    unpack_z_into::<P>(packed_z, z);
+    if z.max() >= P::GAMMA1 - P::BETA {
+        return Err(Error::NormBoundExceeded);
+    }
+
+    unpack_hint_into::<P>(packed_hint, hint)?;
+
+    // Unwrapping is safe here because the slice is of the right size
+    expand_a_into::<P>(Ctr::new(rho.try_into().unwrap()), a);
+
+    for (packed_t1_i, t1_i) in zip(packed_t1.chunks_exact(T1_PACKED_SIZE), &mut *t1) {
+        *t1_i = PlainPolynomial::unpack(packed_t1_i, &t1_unpacker);
+    }
+
+    t1.shift_d_inplace();
+    t1.ntt_into(shifted_t1_hat);
+
+    *challenge = make_challenge::<P>(expected_challenge_seed).into_ntt();
+    *shifted_t1_hat *= &*challenge;
+
+    z.ntt_into(z_hat);
+    a.mul_into(z_hat, k_product);
+    *k_product -= &*shifted_t1_hat;
+    k_product.reduce_32_inplace();
+    k_product.plain_into(w1);
+    w1.caddq_inplace();
+    w1.use_hint_inplace::<P>(hint);
+
+    let mut hasher = Shake256::default();
+    let mut challenge_seed_buf = [0u8; CHALLENGE_SEED_SIZE_MAX];
+    let challenge_seed = &mut challenge_seed_buf[..P::CHALLENGE_SEED_SIZE];
+
+    hasher.update(mu);
+    update_with_w1::<P>(&mut hasher, w1);
+
+    let mut reader = hasher.finalize_xof();
+    reader.read(challenge_seed);
+
+    if challenge_seed == expected_challenge_seed {
+        Ok(())
+    } else {
+        Err(Error::ChallengeMismatch)
+    }
+}
+
+/// Unpacks the polynomials of `packed`, of `packed_size` bytes each, into `dst` in the NTT domain
+fn unpack_ntt_into<N: Dimension>(
+    packed: &[u8],
+    packed_size: usize,
+    dst: &mut Vector<NTTPolynomial, N>,
+    unpack: impl Fn(&[u8]) -> NTTPolynomial,
+) {
+    for (packed_coeff, dst_coeff) in zip(packed.chunks_exact(packed_size), dst) {
+        *dst_coeff = unpack(packed_coeff);
+    }
+}
@@ -0,0 +1,422 @@
+//! X.509 v3 certificates and PKCS#10 certificate signing requests signed with the keys of the crate
+//!
+//! The certificates and the requests are built by the builders of the `x509-cert` crate, which sign
+//! through the `signature` traits of the keys of [`crate::signing`] and [`crate::mldsa`], so
+//! deterministically as [`crate::sign`] does. The functions of this module cover a small private
+//! PKI: a self-signed root, intermediates below it, and device certificates issued from the
+//! requests of the devices.
+//!
+//! Verification selects the scheme and the parameter set from the algorithm identifier of the
+//! issuer key, see [`crate::asn1`], so that a chain may mix them. The AES variant of round-3
+//! Dilithium is verified with [`SoftwareAesCounter`]. Chain validation checks the names, the
+//! signatures, the validity periods, the basic constraints and the key usage of the issuers, and
+//! rejects critical extensions other than these two. It does not check revocation, nor the name
+//! constraints and the policies, which a private PKI of this size does without.
+
+use core::{
+    fmt::{self, Display, Formatter},
+    iter::{once, zip},
+    time::Duration,
+};
+
+use const_oid::AssociatedOid;
+use signature::{Keypair, Signer};
+use spki::{
+    der::{asn1::BitString, Encode},
+    AlgorithmIdentifierOwned, DynSignatureAlgorithmIdentifier, EncodePublicKey,
+    SignatureBitStringEncoding, SubjectPublicKeyInfoOwned,
+};
+use x509_cert::{
+    builder::{self, Builder, CertificateBuilder, Profile, RequestBuilder},
+    ext::pkix::{BasicConstraints, KeyUsage},
+    name::Name,
+    request::CertReq,
+    serial_number::SerialNumber,
+    time::Validity,
+    Certificate,
+};
+
+use crate::{
+    asn1::{
+        CounterVariant, DilithiumOid, MlDsaOid, DILITHIUM2, DILITHIUM2_AES, DILITHIUM3,
+        DILITHIUM3_AES, DILITHIUM5, DILITHIUM5_AES, ML_DSA_44, ML_DSA_65, ML_DSA_87,
+    },
+    counter::{ShakeCounter, SoftwareAesCounter},
+    mldsa, signing, Dilithium2, Dilithium3, Dilithium5, MlDsa44, MlDsa65, MlDsa87, ParameterSet,
+    PublicKey, Signature,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A certificate or a request could not be built or signed
+    Builder(builder::Error),
+    /// A structure could not be encoded
+    Asn1(spki::der::Error),
+    /// A key or a signature is not identified as one of the crate, or the signature algorithm
+    /// differs from the algorithm of the key
+    UnsupportedAlgorithm,
+    /// The bit string of a public key is not a packed public key
+    InvalidPublicKey,
+    /// A signature could not be decoded or does not verify
+    Signature(crate::Error),
+    /// The issuer of a certificate is not the subject of the certificate above it, or the signing
+    /// key is not the one of the issuer certificate
+    IssuerMismatch,
+    /// The time is outside of the validity period of a certificate
+    OutsideValidity,
+    /// An issuer is not a certification authority allowed to sign certificates
+    NotCertificateAuthority,
+    /// More intermediates follow an issuer than its path length constraint allows
+    PathLengthExceeded,
+    /// A certificate carries a critical extension that is not checked
+    UnsupportedCriticalExtension,
+}
+
+/// Signing key that signs certificates and requests through the builders of `x509-cert`
+pub trait CertificateSigner:
+    Keypair<VerifyingKey: EncodePublicKey>
+    + DynSignatureAlgorithmIdentifier
+    + Signer<<Self as CertificateSigner>::Signature>
+{
+    type Signature: SignatureBitStringEncoding;
+}
+
+impl<P: DilithiumOid, Ctr: CounterVariant> CertificateSigner for signing::SigningKey<P, Ctr> {
+    type Signature = Signature<P>;
+}
+
+impl<P: MlDsaOid> CertificateSigner for mldsa::SigningKey<P> {
+    type Signature = Signature<P>;
+}
+
+impl<P: ParameterSet> SignatureBitStringEncoding for Signature<P> {
+    fn to_bitstring(&self) -> spki::der::Result<BitString> {
+        BitString::from_bytes(self.as_bytes())
+    }
+}
+
+/// Issues the self-signed certificate of the root authority of `signer`
+pub fn issue_root<S: CertificateSigner>(
+    signer: &S,
+    subject: Name,
+    serial_number: SerialNumber,
+    validity: Validity,
+) -> Result<Certificate, Error> {
+    let subject_public_key_info = SubjectPublicKeyInfoOwned::from_key(signer.verifying_key())?;
+
+    Ok(CertificateBuilder::new(
+        Profile::Root,
+        serial_number,
+        validity,
+        subject,
+        subject_public_key_info,
+        signer,
+    )?
+    .build::<S::Signature>()?)
+}
+
+/// Issues the certificate of an intermediate authority, signed by `signer`, the key of `issuer`.
+/// No more than `path_len_constraint` intermediates may follow it.
+pub fn issue_intermediate<S: CertificateSigner>(
+    signer: &S,
+    issuer: &Certificate,
+    subject: Name,
+    subject_public_key_info: SubjectPublicKeyInfoOwned,
+    path_len_constraint: Option<u8>,
+    serial_number: SerialNumber,
+    validity: Validity,
+) -> Result<Certificate, Error> {
+    check_signer(signer, issuer)?;
+
+    let profile = Profile::SubCA {
+        issuer: issuer.tbs_certificate.subject.clone(),
+        path_len_constraint,
+    };
+
+    Ok(CertificateBuilder::new(
+        profile,
+        serial_number,
+        validity,
+        subject,
+        subject_public_key_info,
+        signer,
+    )?
+    .build::<S::Signature>()?)
+}
+
+/// Makes the certificate signing request of the key of `signer`, signed by itself
+pub fn make_request<S: CertificateSigner>(signer: &S, subject: Name) -> Result<CertReq, Error> {
+    Ok(RequestBuilder::new(subject, signer)?.build::<S::Signature>()?)
+}
+
+/// Checks that `request` is signed by the key that it carries
+pub fn verify_request(request: &CertReq) -> Result<(), Error> {
+    verify_signature(
+        &request.info.public_key,
+        &request.algorithm,
+        &request.info.to_der()?,
+        &request.signature,
+    )
+}
+
+/// Issues the certificate of the device that made `request`, signed by `signer`, the key of
+/// `issuer`. The request is verified first, and its extensions are not copied.
+pub fn issue_device<S: CertificateSigner>(
+    signer: &S,
+    issuer: &Certificate,
+    request: &CertReq,
+    serial_number: SerialNumber,
+    validity: Validity,
+) -> Result<Certificate, Error> {
+    check_signer(signer, issuer)?;
+    verify_request(request)?;
+
+    let profile = Profile::Leaf {
+        issuer: issuer.tbs_certificate.subject.clone(),
+        enable_key_agreement: false,
+        enable_key_encipherment: false,
+    };
+
+    Ok(CertificateBuilder::new(
+        profile,
+        serial_number,
+        validity,
+        request.info.subject.clone(),
+        request.info.public_key.clone(),
+        signer,
+    )?
+    .build::<S::Signature>()?)
+}
+
+/// Checks that `certificate` names `issuer` as its issuer and is signed by its key
+pub fn verify_certificate(certificate: &Certificate, issuer: &Certificate) -> Result<(), Error> {
+    if certificate.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+        return Err(Error::IssuerMismatch);
+    }
+
+    if certificate.signature_algorithm != certificate.tbs_certificate.signature {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+
+    verify_signature(
+        &issuer.tbs_certificate.subject_public_key_info,
+        &certificate.signature_algorithm,
+        &certificate.tbs_certificate.to_der()?,
+        &certificate.signature,
+    )
+}
+
+/// Validates the chain from the trusted `root` to `device` through `intermediates`, ordered from
+/// the root down, at `now`, the time elapsed since the Unix epoch
+pub fn verify_chain(
+    root: &Certificate,
+    intermediates: &[Certificate],
+    device: &Certificate,
+    now: Duration,
+) -> Result<(), Error> {
+    let chain = once(root).chain(intermediates).chain(once(device));
+
+    for certificate in chain.clone() {
+        check_validity(certificate, now)?;
+        check_critical_extensions(certificate)?;
+    }
+
+    verify_certificate(root, root)?;
+
+    for (index, (issuer, certificate)) in zip(chain.clone(), chain.skip(1)).enumerate() {
+        // The intermediates below the issuer, which its path length constraint bounds
+        check_authority(issuer, intermediates.len() - index)?;
+        verify_certificate(certificate, issuer)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that `signer` holds the key of the certificate of `issuer`
+fn check_signer<S: CertificateSigner>(signer: &S, issuer: &Certificate) -> Result<(), Error> {
+    let subject_public_key_info = SubjectPublicKeyInfoOwned::from_key(signer.verifying_key())?;
+
+    if subject_public_key_info == issuer.tbs_certificate.subject_public_key_info {
+        Ok(())
+    } else {
+        Err(Error::IssuerMismatch)
+    }
+}
+
+fn check_validity(certificate: &Certificate, now: Duration) -> Result<(), Error> {
+    let validity = &certificate.tbs_certificate.validity;
+
+    if validity.not_before.to_unix_duration() <= now && now <= validity.not_after.to_unix_duration()
+    {
+        Ok(())
+    } else {
+        Err(Error::OutsideValidity)
+    }
+}
+
+fn check_critical_extensions(certificate: &Certificate) -> Result<(), Error> {
+    let checked = [BasicConstraints::OID, KeyUsage::OID];
+
+    match certificate
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .any(|extension| extension.critical && !checked.contains(&extension.extn_id))
+    {
+        true => Err(Error::UnsupportedCriticalExtension),
+        false => Ok(()),
+    }
+}
+
+/// Checks that `issuer` may sign certificates, with `following` intermediates below it
+fn check_authority(issuer: &Certificate, following: usize) -> Result<(), Error> {
+    let tbs_certificate = &issuer.tbs_certificate;
+    let basic_constraints = match tbs_certificate.get::<BasicConstraints>()? {
+        Some((_, basic_constraints)) if basic_constraints.ca => basic_constraints,
+        _ => return Err(Error::NotCertificateAuthority),
+    };
+
+    if let Some((_, key_usage)) = tbs_certificate.get::<KeyUsage>()? {
+        if !key_usage.key_cert_sign() {
+            return Err(Error::NotCertificateAuthority);
+        }
+    }
+
+    match basic_constraints.path_len_constraint {
+        Some(path_len_constraint) if following > path_len_constraint.into() => {
+            Err(Error::PathLengthExceeded)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Verifies `signature` of `msg` with the key of `subject_public_key_info`, in the scheme and the
+/// parameter set that they identify
+fn verify_signature(
+    subject_public_key_info: &SubjectPublicKeyInfoOwned,
+    algorithm: &AlgorithmIdentifierOwned,
+    msg: &[u8],
+    signature: &BitString,
+) -> Result<(), Error> {
+    let key_algorithm = &subject_public_key_info.algorithm;
+
+    if algorithm.oid != key_algorithm.oid
+        || algorithm.parameters.is_some()
+        || key_algorithm.parameters.is_some()
+    {
+        return Err(Error::UnsupportedAlgorithm);
+    }
+
+    let pk = subject_public_key_info
+        .subject_public_key
+        .as_bytes()
+        .ok_or(Error::InvalidPublicKey)?;
+    let signature = signature
+        .as_bytes()
+        .ok_or(Error::Signature(crate::Error::MalformedSignature))?;
+
+    match algorithm.oid {
+        ML_DSA_44 => verify_with::<MlDsa44>(pk, msg, signature, |msg, signature, pk| {
+            mldsa::verify(msg, &[], signature, pk)
+        }),
+        ML_DSA_65 => verify_with::<MlDsa65>(pk, msg, signature, |msg, signature, pk| {
+            mldsa::verify(msg, &[], signature, pk)
+        }),
+        ML_DSA_87 => verify_with::<MlDsa87>(pk, msg, signature, |msg, signature, pk| {
+            mldsa::verify(msg, &[], signature, pk)
+        }),
+        DILITHIUM2 => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium2, ShakeCounter>,
+        ),
+        DILITHIUM3 => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium3, ShakeCounter>,
+        ),
+        DILITHIUM5 => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium5, ShakeCounter>,
+        ),
+        DILITHIUM2_AES => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium2, SoftwareAesCounter>,
+        ),
+        DILITHIUM3_AES => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium3, SoftwareAesCounter>,
+        ),
+        DILITHIUM5_AES => verify_with(
+            pk,
+            msg,
+            signature,
+            crate::verify::<Dilithium5, SoftwareAesCounter>,
+        ),
+        _ => Err(Error::UnsupportedAlgorithm),
+    }
+}
+
+fn verify_with<P: ParameterSet>(
+    pk: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+    verify: impl FnOnce(&[u8], &Signature<P>, &PublicKey<P>) -> Result<(), crate::Error>,
+) -> Result<(), Error> {
+    let pk = PublicKey::<P>::from_bytes(pk).map_err(|_| Error::InvalidPublicKey)?;
+    let signature = Signature::<P>::from_bytes(signature)?;
+
+    Ok(verify(msg, &signature, &pk)?)
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Builder(err) => write!(f, "builder error: {err}"),
+            Self::Asn1(err) => write!(f, "ASN.1 error: {err}"),
+            Self::UnsupportedAlgorithm => f.write_str("unsupported algorithm"),
+            Self::InvalidPublicKey => f.write_str("invalid public key"),
+            Self::Signature(err) => write!(f, "signature error: {err}"),
+            Self::IssuerMismatch => f.write_str("issuer mismatch"),
+            Self::OutsideValidity => f.write_str("outside of the validity period"),
+            Self::NotCertificateAuthority => f.write_str("issuer is not a certification authority"),
+            Self::PathLengthExceeded => f.write_str("path length constraint exceeded"),
+            Self::UnsupportedCriticalExtension => f.write_str("unsupported critical extension"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<builder::Error> for Error {
+    fn from(err: builder::Error) -> Self {
+        Self::Builder(err)
+    }
+}
+
+impl From<spki::der::Error> for Error {
+    fn from(err: spki::der::Error) -> Self {
+        Self::Asn1(err)
+    }
+}
+
+impl From<spki::Error> for Error {
+    fn from(err: spki::Error) -> Self {
+        Self::Builder(err.into())
+    }
+}
+
+impl From<crate::Error> for Error {
+    fn from(err: crate::Error) -> Self {
+        Self::Signature(err)
+    }
+}